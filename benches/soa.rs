@@ -0,0 +1,74 @@
+//Per Color conversion against the structure of arrays kernels on a 3840x2160 frame.
+//Run with `cargo bench --bench soa`.
+use prismatic_color::soa::Planes;
+use prismatic_color::{Color, ColorModel};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const WIDTH: usize = 3840;
+const HEIGHT: usize = 2160;
+const RUNS: u32 = 5;
+
+fn frame() -> Vec<f32> {
+    (0..WIDTH * HEIGHT)
+        .flat_map(|i| {
+            let (x, y) = ((i % WIDTH) as f32 / WIDTH as f32, (i / WIDTH) as f32 / HEIGHT as f32);
+            [x, y, (x + y) / 2., 1.]
+        })
+        .collect()
+}
+
+fn time(mut run: impl FnMut()) -> Duration {
+    run();
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        run();
+    }
+    start.elapsed() / RUNS
+}
+
+fn compare(name: &str, pixels: &[f32], from: ColorModel, to: ColorModel, kernel: fn(&mut Planes)) {
+    let colors: Vec<Color> = pixels
+        .chunks_exact(4)
+        .map(|p| Color::from_array([p[0], p[1], p[2], p[3]], from))
+        .collect();
+    let per_color = time(|| {
+        black_box(colors.iter().map(|color| color.convert_color(to)).collect::<Vec<Color>>());
+    });
+    //The kernels work in place, so the input is copied back between runs outside the timing
+    let planes = Planes::from_interleaved(pixels);
+    let mut work = planes.clone();
+    let mut soa = Duration::ZERO;
+    for run in 0..=RUNS {
+        for (channel, source) in work.channels.iter_mut().zip(&planes.channels) {
+            channel.copy_from_slice(source);
+        }
+        let start = Instant::now();
+        kernel(black_box(&mut work));
+        if run > 0 {
+            soa += start.elapsed();
+        }
+    }
+    let soa = soa / RUNS;
+    println!(
+        "{:<24} per Color {:>8.1?}   planes {:>8.1?}   {:>5.2}x",
+        name,
+        per_color,
+        soa,
+        per_color.as_secs_f64() / soa.as_secs_f64()
+    );
+}
+
+fn main() {
+    let rgb = frame();
+    let mut hsv = Planes::from_interleaved(&rgb);
+    hsv.rgb_to_cubic_hsv();
+    let mut hcl = Planes::from_interleaved(&rgb);
+    hcl.rgb_to_spherical_hcl();
+
+    compare("rgb_to_cubic_hsv", &rgb, ColorModel::RGBA, ColorModel::CubicHSVA, Planes::rgb_to_cubic_hsv);
+    compare("cubic_hsv_to_rgb", &hsv.to_interleaved(), ColorModel::CubicHSVA, ColorModel::RGBA, Planes::cubic_hsv_to_rgb);
+    compare("rgb_to_spherical_hcl", &rgb, ColorModel::RGBA, ColorModel::SphericalHCLA, Planes::rgb_to_spherical_hcl);
+    compare("spherical_hcl_to_rgb", &hcl.to_interleaved(), ColorModel::SphericalHCLA, ColorModel::RGBA, Planes::spherical_hcl_to_rgb);
+    compare("rgb_to_yuv", &rgb, ColorModel::RGBA, ColorModel::YUVA, Planes::rgb_to_yuv);
+}
@@ -0,0 +1,119 @@
+use crate::cie::xy_to_xyz;
+use crate::transformations::{invert_matrix, matrix_transform, multiply_matrices};
+
+type Matrix = [[f32; 3]; 3];
+
+//CIE 1931 2° standard illuminant white points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhitePoint {
+    A,
+    D50,
+    D55,
+    D65,
+    D75,
+    E,
+    F2,
+    F11,
+}
+
+impl WhitePoint {
+    pub fn xy(&self) -> [f32; 2] {
+        match self {
+            WhitePoint::A => [0.44757, 0.40745],
+            WhitePoint::D50 => [0.34567, 0.3585],
+            WhitePoint::D55 => [0.33242, 0.34743],
+            WhitePoint::D65 => [0.31271, 0.32902],
+            WhitePoint::D75 => [0.29902, 0.31485],
+            WhitePoint::E => [1. / 3., 1. / 3.],
+            WhitePoint::F2 => [0.37208, 0.37529],
+            WhitePoint::F11 => [0.38052, 0.37713],
+        }
+    }
+
+    //XYZ with Y = 1
+    pub fn xyz(&self) -> [f32; 3] {
+        xy_to_xyz(self.xy())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdaptationMethod {
+    //Hunt-Pointer-Estevez cone space
+    VonKries,
+    Bradford,
+    Cat02,
+    Cat16,
+}
+
+impl AdaptationMethod {
+    //XYZ to the method's cone response space
+    pub fn cone_matrix(&self) -> Matrix {
+        match self {
+            AdaptationMethod::VonKries => [[0.40024, 0.7076, -0.08081], [-0.2263, 1.16532, 0.0457], [0., 0., 0.91822]],
+            AdaptationMethod::Bradford => [[0.8951, 0.2664, -0.1614], [-0.7502, 1.7135, 0.0367], [0.0389, -0.0685, 1.0296]],
+            AdaptationMethod::Cat02 => [[0.7328, 0.4296, -0.1624], [-0.7036, 1.6975, 0.0061], [0.003, 0.0136, 0.9834]],
+            AdaptationMethod::Cat16 => [[0.401288, 0.650173, -0.051461], [-0.250268, 1.204414, 0.045854], [-0.002079, 0.048952, 0.953127]],
+        }
+    }
+
+    //Full adaptation from one white to another, whites given as xy chromaticities
+    pub fn adaptation_matrix(&self, from: [f32; 2], to: [f32; 2]) -> Matrix {
+        let cone = self.cone_matrix();
+        let source = matrix_transform(&cone, xy_to_xyz(from));
+        let destination = matrix_transform(&cone, xy_to_xyz(to));
+        let scale = [
+            [destination[0] / source[0], 0., 0.],
+            [0., destination[1] / source[1], 0.],
+            [0., 0., destination[2] / source[2]],
+        ];
+        multiply_matrices(&invert_matrix(&cone), &multiply_matrices(&scale, &cone))
+    }
+}
+
+pub fn adapt_xyz(xyz: [f32; 3], from: [f32; 2], to: [f32; 2], method: AdaptationMethod) -> [f32; 3] {
+    if from == to {
+        return xyz;
+    }
+    matrix_transform(&method.adaptation_matrix(from, to), xyz)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+    use crate::rgb_space::RgbSpace;
+
+    #[test]
+    fn test_bradford_d65_to_d50() {
+        //Lindbloom's published matrix
+        let expected = [
+            [1.0478112, 0.0228866, -0.050127],
+            [0.0295424, 0.9904844, -0.0170491],
+            [-0.0092345, 0.0150436, 0.7521316],
+        ];
+        let matrix = AdaptationMethod::Bradford.adaptation_matrix(WhitePoint::D65.xy(), WhitePoint::D50.xy());
+        for (row, expected) in matrix.iter().zip(expected.iter()) {
+            for (value, expected) in row.iter().zip(expected.iter()) {
+                assert!((value - expected).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_whites_map_to_whites() {
+        let whites = [WhitePoint::A, WhitePoint::D50, WhitePoint::D55, WhitePoint::D65, WhitePoint::D75, WhitePoint::E, WhitePoint::F2, WhitePoint::F11];
+        for method in [AdaptationMethod::VonKries, AdaptationMethod::Bradford, AdaptationMethod::Cat02, AdaptationMethod::Cat16] {
+            for from in whites {
+                for to in whites {
+                    let adapted = adapt_xyz(from.xyz(), from.xy(), to.xy(), method);
+                    for (a, b) in adapted.iter().zip(to.xyz().iter()) {
+                        assert!((a - b).abs() < 1e-4);
+                    }
+                }
+            }
+        }
+        //D65 screen white stays white in the D50 ProPhoto space
+        let white = Color::rgb(1., 1., 1.).convert_rgb_space(&RgbSpace::SRGB, &RgbSpace::PROPHOTO);
+        assert!(white.to_array().iter().all(|c| (c - 1.).abs() < 1e-3));
+    }
+}
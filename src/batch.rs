@@ -0,0 +1,145 @@
+use crate::*;
+use num_traits::{AsPrimitive, PrimInt, Unsigned};
+
+type Kernel = fn([f32; 4]) -> [f32; 4];
+
+//A model to model conversion resolved once, then applied to any number of pixels.
+//Gives the same result as Color::convert_color.
+#[derive(Clone, Copy)]
+pub(crate) struct Conversion {
+    to_rgb: Kernel,
+    from_rgb: Kernel,
+    wrap_input: bool,
+    wrap_output: bool,
+    identity: bool,
+}
+
+impl Conversion {
+    pub(crate) fn new(from: ColorModel, to: ColorModel) -> Conversion {
+        let to_rgb: Kernel = match from {
+            ColorModel::RGBA => |c| c,
+            ColorModel::CMYA => cmy_to_rgb,
+            ColorModel::RGBW => rgbw_to_rgb,
+            ColorModel::CMYK => cmyk_to_rgb,
+            ColorModel::SphericalHCLA => spherical_hcl_to_rgb,
+            ColorModel::SphericalHWBA => spherical_hwb_to_rgb,
+            ColorModel::CubicHSLA => cubic_hsl_to_rgb,
+            ColorModel::CubicHSVA => cubic_hsv_to_rgb,
+            ColorModel::CubicHWBA => cubic_hwb_to_rgb,
+            ColorModel::YUVA => yuv_to_rgb,
+        };
+        let from_rgb: Kernel = match to {
+            ColorModel::RGBA => |c| c,
+            ColorModel::CMYA => rgb_to_cmy,
+            ColorModel::RGBW => rgb_to_rgbw,
+            ColorModel::CMYK => rgb_to_cmyk,
+            ColorModel::SphericalHCLA => rgb_to_spherical_hcl,
+            ColorModel::SphericalHWBA => rgb_to_spherical_hwb,
+            ColorModel::CubicHSLA => rgb_to_hsl,
+            ColorModel::CubicHSVA => rgb_to_cubic_hsv,
+            ColorModel::CubicHWBA => rgb_to_cubic_hwb,
+            ColorModel::YUVA => rgb_to_yuv,
+        };
+        Conversion {
+            to_rgb,
+            from_rgb,
+            wrap_input: from.is_cylindrical(),
+            wrap_output: to.is_cylindrical(),
+            identity: from == to,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn apply(&self, pixel: [f32; 4]) -> [f32; 4] {
+        if self.identity {
+            return pixel;
+        }
+        let pixel = if self.wrap_input { wrap_hue(pixel) } else { pixel };
+        let pixel = (self.from_rgb)((self.to_rgb)(pixel));
+        if self.wrap_output { wrap_hue(pixel) } else { pixel }
+    }
+}
+
+fn wrap_hue(mut components: [f32; 4]) -> [f32; 4] {
+    if components[0] >= 1. {
+        components[0] -= 1.;
+    }
+    components
+}
+
+//Converts interleaved four component pixels, input and output must have the same length
+pub fn convert_slice(input: &[f32], from: ColorModel, to: ColorModel, output: &mut [f32]) {
+    assert_eq!(input.len(), output.len(), "input and output must be the same length");
+    assert_eq!(input.len() % 4, 0, "pixels have four components");
+    let conversion = Conversion::new(from, to);
+    for (source, destination) in input.chunks_exact(4).zip(output.chunks_exact_mut(4)) {
+        let pixel = conversion.apply([source[0], source[1], source[2], source[3]]);
+        destination.copy_from_slice(&pixel);
+    }
+}
+
+//Integer pixels are read as 0 to 1 over the type's full range.
+//Components that leave 0 to 1 in the target model, like YUV chroma, saturate.
+pub fn convert_integer_slice<T>(input: &[T], from: ColorModel, to: ColorModel, output: &mut [T])
+where
+    T: Unsigned + PrimInt + AsPrimitive<f32> + 'static,
+    f32: AsPrimitive<T>,
+{
+    assert_eq!(input.len(), output.len(), "input and output must be the same length");
+    assert_eq!(input.len() % 4, 0, "pixels have four components");
+    let conversion = Conversion::new(from, to);
+    let scale: f32 = T::max_value().as_();
+    for (source, destination) in input.chunks_exact(4).zip(output.chunks_exact_mut(4)) {
+        let pixel = conversion.apply([0, 1, 2, 3].map(|i| source[i].as_() / scale));
+        for (value, component) in destination.iter_mut().zip(pixel) {
+            *value = (component.clamp(0., 1.) * scale).round().as_();
+        }
+    }
+}
+
+pub fn convert_rgba8(input: &[u8], from: ColorModel, to: ColorModel, output: &mut [u8]) {
+    convert_integer_slice(input, from, to, output)
+}
+
+pub fn convert_rgba16(input: &[u16], from: ColorModel, to: ColorModel, output: &mut [u16]) {
+    convert_integer_slice(input, from, to, output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODELS: [ColorModel; 10] = [
+        ColorModel::RGBA, ColorModel::CMYA, ColorModel::RGBW, ColorModel::CMYK, ColorModel::SphericalHCLA,
+        ColorModel::SphericalHWBA, ColorModel::CubicHSLA, ColorModel::CubicHSVA, ColorModel::CubicHWBA, ColorModel::YUVA,
+    ];
+
+    #[test]
+    fn test_matches_convert_color() {
+        let colors = [Color::rgb(0.9, 0.2, 0.1), Color::rgb(0.1, 0.5, 0.7).set_alpha(0.5), Color::rgb(1., 1., 1.), Color::rgb(0.05, 0.05, 0.1)];
+        for from in MODELS {
+            let sources: Vec<Color> = colors.iter().map(|color| color.convert_color(from)).collect();
+            let input: Vec<f32> = sources.iter().flat_map(|color| color.to_array()).collect();
+            for to in MODELS {
+                let mut output = vec![0.; input.len()];
+                convert_slice(&input, from, to, &mut output);
+                let expected: Vec<f32> = sources.iter().flat_map(|color| color.convert_color(to).to_array()).collect();
+                assert_eq!(output, expected, "{:?} to {:?}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn test_integer_round_trip() {
+        let input: Vec<u8> = (0..=255u8).flat_map(|v| [v, 255 - v, v / 2, 200]).collect();
+        let (mut hsv, mut back) = (vec![0; input.len()], vec![0; input.len()]);
+        convert_rgba8(&input, ColorModel::RGBA, ColorModel::CubicHSVA, &mut hsv);
+        convert_rgba8(&hsv, ColorModel::CubicHSVA, ColorModel::RGBA, &mut back);
+        assert!(input.iter().zip(back.iter()).all(|(a, b)| a.abs_diff(*b) <= 3));
+
+        let input: Vec<u16> = vec![65535, 0, 0, 65535, 1000, 40000, 20000, 0];
+        let mut output = vec![0; input.len()];
+        convert_rgba16(&input, ColorModel::RGBA, ColorModel::CMYA, &mut output);
+        assert_eq!(output, vec![0, 65535, 65535, 65535, 64535, 25535, 45535, 0]);
+    }
+}
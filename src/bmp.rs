@@ -0,0 +1,150 @@
+use crate::image::Image;
+use crate::lut3d::invalid_data;
+use std::io::{self, Read, Write};
+
+const FILE_HEADER: usize = 14;
+const INFO_HEADER: usize = 40;
+const V4_HEADER: usize = 108;
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+
+fn u16_at(data: &[u8], offset: usize) -> io::Result<u16> {
+    let bytes = data.get(offset..offset + 2).ok_or_else(|| invalid_data("BMP header ends early"))?;
+    Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> io::Result<u32> {
+    let bytes = data.get(offset..offset + 4).ok_or_else(|| invalid_data("BMP header ends early"))?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+//Uncompressed 24 and 32 bit BMPs, bottom up or top down, with BI_BITFIELDS masks.
+//32 bit files whose alpha bytes are all zero are read as opaque, as most writers leave them unset.
+pub fn read_bmp(mut reader: impl Read) -> io::Result<Image> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    if data.get(..2) != Some(b"BM") {
+        return Err(invalid_data("not a BMP file"));
+    }
+    let offset = u32_at(&data, 10)? as usize;
+    let header_size = u32_at(&data, FILE_HEADER)? as usize;
+    let width = u32_at(&data, FILE_HEADER + 4)? as i32;
+    let height = u32_at(&data, FILE_HEADER + 8)? as i32;
+    let bits = u16_at(&data, FILE_HEADER + 14)?;
+    let compression = u32_at(&data, FILE_HEADER + 16)?;
+    if header_size < INFO_HEADER || width <= 0 || height == 0 || !(bits == 24 || bits == 32) {
+        return Err(invalid_data("only 24 and 32 bit BMPs are supported"));
+    }
+    let masks = match (compression, bits) {
+        (BI_RGB, 24) => [0xff0000, 0xff00, 0xff, 0],
+        (BI_RGB, 32) => [0xff0000, 0xff00, 0xff, 0xff000000],
+        (BI_BITFIELDS, 32) => {
+            //Masks follow a plain info header, or sit inside the larger headers
+            let alpha = if header_size >= V4_HEADER { u32_at(&data, FILE_HEADER + 52)? } else { 0 };
+            [u32_at(&data, FILE_HEADER + 40)?, u32_at(&data, FILE_HEADER + 44)?, u32_at(&data, FILE_HEADER + 48)?, alpha]
+        }
+        _ => return Err(invalid_data("compressed BMPs are not supported")),
+    };
+
+    let (width, rows) = (width as usize, height.unsigned_abs() as usize);
+    let bytes_per_pixel = bits as usize / 8;
+    let stride = (width * bytes_per_pixel).div_ceil(4) * 4;
+    let body = data.get(offset..offset + stride * rows).ok_or_else(|| invalid_data("BMP pixel data ends early"))?;
+    let channel = |pixel: u32, mask: u32| {
+        if mask == 0 {
+            return 1.;
+        }
+        ((pixel & mask) >> mask.trailing_zeros()) as f32 / (mask >> mask.trailing_zeros()) as f32
+    };
+    let mut pixels = Vec::with_capacity(width * rows * 4);
+    for y in 0..rows {
+        //Positive heights are stored bottom row first
+        let row = if height > 0 { rows - 1 - y } else { y };
+        for x in 0..width {
+            let start = row * stride + x * bytes_per_pixel;
+            let bytes = &body[start..start + bytes_per_pixel];
+            let pixel = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes.get(3).copied().unwrap_or(0)]);
+            pixels.extend(masks.map(|mask| channel(pixel, mask)));
+        }
+    }
+    if masks[3] != 0 && pixels.chunks_exact(4).all(|p| p[3] == 0.) {
+        pixels.chunks_exact_mut(4).for_each(|p| p[3] = 1.);
+    }
+    Ok(Image::new(width, rows, pixels))
+}
+
+impl Image {
+    //24 bit when every pixel is opaque, otherwise 32 bit with a V4 header so readers honour the alpha mask
+    pub fn write_bmp(&self, mut writer: impl Write) -> io::Result<()> {
+        let opaque = self.is_opaque();
+        let (bits, header_size) = if opaque { (24u16, INFO_HEADER) } else { (32, V4_HEADER) };
+        let bytes_per_pixel = bits as usize / 8;
+        let stride = (self.width * bytes_per_pixel).div_ceil(4) * 4;
+        let offset = FILE_HEADER + header_size;
+        let size = offset + stride * self.height;
+        if size > u32::MAX as usize || self.width > i32::MAX as usize || self.height > i32::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "image is too large for BMP"));
+        }
+
+        let mut data = Vec::with_capacity(size);
+        data.extend_from_slice(b"BM");
+        data.extend((size as u32).to_le_bytes());
+        data.extend([0; 4]);
+        data.extend((offset as u32).to_le_bytes());
+        data.extend((header_size as u32).to_le_bytes());
+        data.extend((self.width as i32).to_le_bytes());
+        data.extend((self.height as i32).to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend(bits.to_le_bytes());
+        data.extend((if opaque { BI_RGB } else { BI_BITFIELDS }).to_le_bytes());
+        data.extend(((stride * self.height) as u32).to_le_bytes());
+        //2835 pixels per meter is 72 dpi
+        data.extend(2835u32.to_le_bytes());
+        data.extend(2835u32.to_le_bytes());
+        data.extend([0; 8]);
+        if !opaque {
+            for mask in [0xff0000u32, 0xff00, 0xff, 0xff000000] {
+                data.extend(mask.to_le_bytes());
+            }
+            data.extend(b"BGRs");
+            data.extend([0; 48]);
+        }
+
+        let rgba = self.to_rgba8();
+        for y in (0..self.height).rev() {
+            let row = &rgba[y * self.width * 4..(y + 1) * self.width * 4];
+            for p in row.chunks_exact(4) {
+                data.extend([p[2], p[1], p[0]]);
+                if !opaque {
+                    data.push(p[3]);
+                }
+            }
+            data.resize(data.len() + stride - self.width * bytes_per_pixel, 0);
+        }
+        writer.write_all(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let pixels: Vec<u8> = (0..15u8).flat_map(|i| [i * 17, 255 - i * 10, i, 255]).collect();
+        let opaque = Image::from_integers(5, 3, &pixels);
+        let mut data = Vec::new();
+        opaque.write_bmp(&mut data).unwrap();
+        //Rows of 15 bytes are padded to 16
+        assert_eq!(data.len(), FILE_HEADER + INFO_HEADER + 16 * 3);
+        assert_eq!(read_bmp(&data[..]).unwrap().to_rgba8(), pixels);
+
+        let mut translucent = pixels.clone();
+        translucent[7] = 100;
+        let image = Image::from_integers(5, 3, &translucent);
+        let mut data = Vec::new();
+        image.write_bmp(&mut data).unwrap();
+        assert_eq!(u16_at(&data, FILE_HEADER + 14).unwrap(), 32);
+        assert_eq!(read_bmp(&data[..]).unwrap().to_rgba8(), translucent);
+    }
+}
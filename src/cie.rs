@@ -0,0 +1,144 @@
+use crate::Color;
+use crate::transformations::{matrix_transform, srgb_to_linear};
+use std::f32::consts::PI;
+
+//D65 reference white, Y normalized to 1
+pub const D65_WHITE: [f32; 3] = [0.95047, 1., 1.08883];
+
+const SRGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.4124564, 0.3575761, 0.1804375],
+    [0.2126729, 0.7151522, 0.0721750],
+    [0.0193339, 0.119192, 0.9503041],
+];
+
+impl Color {
+    //CIE XYZ of the sRGB value, D65 white
+    pub fn to_xyz(&self) -> [f32; 3] {
+        let [r, g, b, _] = self.to_rgb().components;
+        matrix_transform(&SRGB_TO_XYZ, [r, g, b].map(srgb_to_linear))
+    }
+
+    //CIELAB relative to D65
+    pub fn to_lab(&self) -> [f32; 3] {
+        xyz_to_lab(self.to_xyz(), D65_WHITE)
+    }
+
+    pub fn delta_e(&self, other: Color) -> f32 {
+        delta_e_2000(self.to_lab(), other.to_lab())
+    }
+}
+
+//Chromaticity to XYZ with Y = 1
+pub fn xy_to_xyz(xy: [f32; 2]) -> [f32; 3] {
+    let [x, y] = xy;
+    [x / y, 1., (1. - x - y) / y]
+}
+
+pub fn xyz_to_lab(xyz: [f32; 3], white: [f32; 3]) -> [f32; 3] {
+    let f = |t: f32| {
+        if t > 216. / 24389. {
+            t.cbrt()
+        } else {
+            (24389. / 27. * t + 16.) / 116.
+        }
+    };
+    let [x, y, z] = [0, 1, 2].map(|i| f(xyz[i] / white[i]));
+    [116. * y - 16., 500. * (x - y), 200. * (y - z)]
+}
+
+pub fn lab_to_xyz(lab: [f32; 3], white: [f32; 3]) -> [f32; 3] {
+    let [l, a, b] = lab;
+    let fy = (l + 16.) / 116.;
+    let fx = fy + a / 500.;
+    let fz = fy - b / 200.;
+    let f_inv = |t: f32| {
+        if t.powi(3) > 216. / 24389. {
+            t.powi(3)
+        } else {
+            (116. * t - 16.) * 27. / 24389.
+        }
+    };
+    [f_inv(fx) * white[0], f_inv(fy) * white[1], f_inv(fz) * white[2]]
+}
+
+//Euclidean distance in Lab
+pub fn delta_e_76(a: [f32; 3], b: [f32; 3]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+//CIEDE2000 with unit weighting factors
+pub fn delta_e_2000(lab_1: [f32; 3], lab_2: [f32; 3]) -> f32 {
+    let [l1, a1, b1] = lab_1;
+    let [l2, a2, b2] = lab_2;
+    let c_bar = ((a1 * a1 + b1 * b1).sqrt() + (a2 * a2 + b2 * b2).sqrt()) / 2.;
+    let g = 0.5 * (1. - (c_bar.powi(7) / (c_bar.powi(7) + 25f32.powi(7))).sqrt());
+    let (a1, a2) = (a1 * (1. + g), a2 * (1. + g));
+    let (c1, c2) = ((a1 * a1 + b1 * b1).sqrt(), (a2 * a2 + b2 * b2).sqrt());
+    let hue = |b: f32, a: f32| if a == 0. && b == 0. { 0. } else { b.atan2(a).rem_euclid(2. * PI) };
+    let (h1, h2) = (hue(b1, a1), hue(b2, a2));
+
+    let delta_l = l2 - l1;
+    let delta_c = c2 - c1;
+    let delta_h = if c1 * c2 == 0. {
+        0.
+    } else if (h2 - h1).abs() <= PI {
+        h2 - h1
+    } else if h2 - h1 > PI {
+        h2 - h1 - 2. * PI
+    } else {
+        h2 - h1 + 2. * PI
+    };
+    let delta_hue = 2. * (c1 * c2).sqrt() * (delta_h / 2.).sin();
+
+    let l_bar = (l1 + l2) / 2.;
+    let c_bar = (c1 + c2) / 2.;
+    let h_bar = if c1 * c2 == 0. {
+        h1 + h2
+    } else if (h1 - h2).abs() <= PI {
+        (h1 + h2) / 2.
+    } else if h1 + h2 < 2. * PI {
+        (h1 + h2 + 2. * PI) / 2.
+    } else {
+        (h1 + h2 - 2. * PI) / 2.
+    };
+    let t = 1. - 0.17 * (h_bar - PI / 6.).cos() + 0.24 * (2. * h_bar).cos()
+        + 0.32 * (3. * h_bar + PI / 30.).cos()
+        - 0.20 * (4. * h_bar - 63f32.to_radians()).cos();
+    let delta_theta = 30f32.to_radians() * (-((h_bar.to_degrees() - 275.) / 25.).powi(2)).exp();
+    let r_c = 2. * (c_bar.powi(7) / (c_bar.powi(7) + 25f32.powi(7))).sqrt();
+    let s_l = 1. + 0.015 * (l_bar - 50.).powi(2) / (20. + (l_bar - 50.).powi(2)).sqrt();
+    let s_c = 1. + 0.045 * c_bar;
+    let s_h = 1. + 0.015 * c_bar * t;
+    let r_t = -(2. * delta_theta).sin() * r_c;
+
+    let (l, c, h) = (delta_l / s_l, delta_c / s_c, delta_hue / s_h);
+    (l * l + c * c + h * h + r_t * c * h).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lab_white_and_round_trip() {
+        let lab = Color::rgb(1., 1., 1.).to_lab();
+        assert!((lab[0] - 100.).abs() < 1e-2 && lab[1].abs() < 1e-2 && lab[2].abs() < 1e-2);
+        let lab = [52., 31., -18.];
+        let back = xyz_to_lab(lab_to_xyz(lab, D65_WHITE), D65_WHITE);
+        assert!(delta_e_76(lab, back) < 1e-3);
+    }
+
+    #[test]
+    fn test_delta_e_2000_reference_pairs() {
+        //Sharma, Wu and Dalal test data
+        let pairs = [
+            ([50., 2.6772, -79.7751], [50., 0., -82.7485], 2.0425),
+            ([50., -1.3802, -84.2814], [50., 0., -82.7485], 1.0),
+            ([50., 2.5, 0.], [73., 25., -18.], 27.1492),
+            ([60.2574, -34.0099, 36.2677], [60.4626, -34.1751, 39.4387], 1.2644),
+        ];
+        for (a, b, expected) in pairs {
+            assert!((delta_e_2000(a, b) - expected).abs() < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use crate::{Color, ColorModel};
+use crate::theme::{TonalPalette, THREE_SQRT};
+use crate::transformations::srgb_to_linear;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextSize {
+    Normal,
+    //18pt and up, or 14pt bold
+    Large,
+}
+
+impl TextSize {
+    pub fn aa_ratio(&self) -> f32 {
+        match self {
+            TextSize::Normal => 4.5,
+            TextSize::Large => 3.,
+        }
+    }
+
+    pub fn aaa_ratio(&self) -> f32 {
+        match self {
+            TextSize::Normal => 7.,
+            TextSize::Large => 4.5,
+        }
+    }
+}
+
+//Contrast is measured on the sRGB value of any model, alpha is ignored
+impl Color {
+    //WCAG 2.x relative luminance
+    pub fn relative_luminance(&self) -> f32 {
+        let [r, g, b, _] = self.to_rgb().components.map(|c| srgb_to_linear(c.clamp(0., 1.)));
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    pub fn contrast_ratio(&self, other: Color) -> f32 {
+        contrast_ratio(*self, other)
+    }
+
+    pub fn meets_aa(&self, other: Color, text_size: TextSize) -> bool {
+        contrast_ratio(*self, other) >= text_size.aa_ratio()
+    }
+
+    pub fn meets_aaa(&self, other: Color, text_size: TextSize) -> bool {
+        contrast_ratio(*self, other) >= text_size.aaa_ratio()
+    }
+}
+
+//WCAG 2.x contrast ratio, from 1 to 21 regardless of argument order
+pub fn contrast_ratio(a: Color, b: Color) -> f32 {
+    let (a, b) = (a.relative_luminance(), b.relative_luminance());
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+//APCA 0.0.98G lightness contrast (Lc).
+//Positive for dark text on a light background, negative for light text on a dark background.
+pub fn apca_contrast(text: Color, background: Color) -> f32 {
+    let text = apca_luminance(text);
+    let background = apca_luminance(background);
+    if (background - text).abs() < 0.0005 {
+        return 0.;
+    }
+    let lc = if background > text {
+        let sapc = (background.powf(0.56) - text.powf(0.57)) * 1.14;
+        if sapc < 0.1 { 0. } else { sapc - 0.027 }
+    } else {
+        let sapc = (background.powf(0.65) - text.powf(0.62)) * 1.14;
+        if sapc > -0.1 { 0. } else { sapc + 0.027 }
+    };
+    lc * 100.
+}
+
+//Screen luminance estimate used by APCA, with the soft clamp near black
+fn apca_luminance(color: Color) -> f32 {
+    let [r, g, b, _] = color.to_rgb().components.map(|c| c.clamp(0., 1.).powf(2.4));
+    let y = 0.2126729 * r + 0.7151522 * g + 0.072175 * b;
+    if y < 0.022 {
+        y + (0.022 - y).powf(1.414)
+    } else {
+        y
+    }
+}
+
+//Pairwise WCAG ratios, matrix[i][j] is the contrast of colors[i] against colors[j]
+pub fn contrast_matrix(colors: &[Color]) -> Vec<Vec<f32>> {
+    colors
+        .iter()
+        .map(|a| colors.iter().map(|b| contrast_ratio(*a, *b)).collect())
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContrastTarget {
+    //WCAG 2.x ratio, e.g. 4.5
+    Wcag(f32),
+    //Absolute APCA Lc, e.g. 60
+    Apca(f32),
+}
+
+impl ContrastTarget {
+    pub fn measure(&self, foreground: Color, background: Color) -> f32 {
+        match self {
+            ContrastTarget::Wcag(_) => contrast_ratio(foreground, background),
+            ContrastTarget::Apca(_) => apca_contrast(foreground, background).abs(),
+        }
+    }
+
+    pub fn is_met(&self, foreground: Color, background: Color) -> bool {
+        match self {
+            ContrastTarget::Wcag(target) | ContrastTarget::Apca(target) => {
+                self.measure(foreground, background) >= *target
+            }
+        }
+    }
+}
+
+//Moves the foreground luminance in spherical HCL until it reaches the target against the background.
+//Hue is kept and chroma only drops where the new luminance would leave the RGB cube.
+//The smallest luminance change in either direction wins. If neither direction reaches the target
+//the end with the most contrast comes back as the error. The result is in the foreground's model.
+pub fn fix_contrast(foreground: Color, background: Color, target: ContrastTarget) -> Result<Color, Color> {
+    if target.is_met(foreground, background) {
+        return Ok(foreground);
+    }
+    let [hue, chroma, luminance, alpha] = foreground.convert_color(ColorModel::SphericalHCLA).components;
+    let palette = TonalPalette::new(hue, chroma);
+    let start = (luminance / THREE_SQRT).clamp(0., 1.);
+    let color_at = |tone: f32| palette.tone(tone).set_alpha(alpha);
+
+    let solve = |end: f32| {
+        if !target.is_met(color_at(end), background) {
+            return None;
+        }
+        //Bisect between the failing start and the passing end
+        let (mut failing, mut passing) = (start, end);
+        for _ in 0..24 {
+            let mid = (failing + passing) / 2.;
+            if target.is_met(color_at(mid), background) {
+                passing = mid;
+            } else {
+                failing = mid;
+            }
+        }
+        Some(passing)
+    };
+
+    let in_model = |tone: f32| color_at(tone).convert_color(foreground.color_type);
+    match (solve(1.), solve(0.)) {
+        (Some(lighter), Some(darker)) => {
+            Ok(in_model(if lighter - start <= start - darker { lighter } else { darker }))
+        }
+        (Some(lighter), None) => Ok(in_model(lighter)),
+        (None, Some(darker)) => Ok(in_model(darker)),
+        (None, None) => {
+            let (lighter, darker) = (color_at(1.), color_at(0.));
+            Err(in_model(if target.measure(lighter, background) >= target.measure(darker, background) { 1. } else { 0. }))
+        }
+    }
+}
+
+//Candidate with the highest WCAG contrast against the background
+pub fn best_text_color(background: Color, candidates: &[Color]) -> Option<Color> {
+    candidates.iter().copied().max_by(|a, b| {
+        contrast_ratio(*a, background).total_cmp(&contrast_ratio(*b, background))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::QUATERNARY_COLORS;
+
+    #[test]
+    fn test_black_and_white() {
+        let (black, white) = (Color::rgb(0., 0., 0.), Color::rgb(1., 1., 1.));
+        assert!((contrast_ratio(black, white) - 21.).abs() < 1e-3);
+        assert!((apca_contrast(black, white) - 106.04).abs() < 0.05);
+        assert!((apca_contrast(white, black) + 107.88).abs() < 0.05);
+        assert!(black.meets_aaa(white, TextSize::Normal));
+        assert!(!Color::rgb(0.6, 0.6, 0.6).meets_aa(white, TextSize::Normal));
+    }
+
+    #[test]
+    fn test_quaternary_matrix() {
+        let colors: Vec<Color> = QUATERNARY_COLORS.iter().flatten().copied().collect();
+        let matrix = contrast_matrix(&colors);
+        for (i, row) in matrix.iter().enumerate() {
+            assert_eq!(row[i], 1.);
+            for (j, ratio) in row.iter().enumerate() {
+                assert_eq!(*ratio, matrix[j][i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fix_contrast() {
+        let background = Color::rgb(1., 1., 1.);
+        let foreground = Color::spherical_hcl(0.6, 0.7, 1.2);
+        for target in [ContrastTarget::Wcag(4.5), ContrastTarget::Wcag(7.), ContrastTarget::Apca(75.)] {
+            let fixed = fix_contrast(foreground, background, target).unwrap();
+            assert!(target.is_met(fixed, background));
+            assert_eq!(fixed.color_type, ColorModel::SphericalHCLA);
+            assert_eq!(fixed.components[0], 0.6);
+        }
+
+        //Nothing on a mid grey reaches 7:1, black gets closest
+        let (foreground, background) = (Color::rgb(115. / 255., 128. / 255., 128. / 255.), Color::rgb(128. / 255., 128. / 255., 128. / 255.));
+        let closest = fix_contrast(foreground, background, ContrastTarget::Wcag(7.)).unwrap_err();
+        assert!(contrast_ratio(closest, background) < 7.);
+        assert_eq!(closest.to_rgb().to_array()[..3], [0., 0., 0.]);
+    }
+
+    #[test]
+    fn test_best_text_color() {
+        let candidates = [Color::rgb(1., 1., 1.), Color::rgb(0., 0., 0.), Color::rgb(0.5, 0.5, 0.5)];
+        assert_eq!(best_text_color(Color::rgb(0.1, 0.1, 0.3), &candidates), Some(candidates[0]));
+        assert_eq!(best_text_color(Color::rgb(0.9, 0.9, 0.6), &candidates), Some(candidates[1]));
+        assert_eq!(best_text_color(Color::rgb(0.9, 0.9, 0.6), &[]), None);
+    }
+}
@@ -0,0 +1,339 @@
+use crate::{Color, ColorModel};
+use crate::cie::delta_e_2000;
+use crate::constants::QUATERNARY_COLORS;
+use crate::transformations::{lerp, linear_to_srgb, matrix_transform, srgb_to_linear};
+
+type Matrix = [[f32; 3]; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deficiency {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+    Achromatopsia,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CvdMethod {
+    //Machado, Oliveira and Fernandes 2009, tabulated per tenth of severity
+    Machado,
+    //Viénot 1999 for protanopia and deuteranopia, Brettel 1997 for tritanopia
+    BrettelVienot,
+}
+
+//Simulation happens in linear sRGB, the result comes back in the color's own model
+impl Color {
+    pub fn simulate_cvd(&self, deficiency: Deficiency, severity: f32) -> Color {
+        self.simulate_cvd_with(deficiency, severity, CvdMethod::Machado)
+    }
+
+    pub fn simulate_cvd_with(&self, deficiency: Deficiency, severity: f32, method: CvdMethod) -> Color {
+        let simulation = Simulation::new(deficiency, severity, method);
+        let [r, g, b, a] = self.to_rgb().components;
+        let [r, g, b] = simulation.apply([r, g, b]);
+        Color::from_array([r, g, b, a], ColorModel::RGBA).convert_color(self.color_type)
+    }
+
+    //Fidaner et al. daltonization, the simulated loss is shifted onto channels the viewer still sees
+    pub fn daltonize(&self, deficiency: Deficiency, severity: f32) -> Color {
+        let simulation = Simulation::new(deficiency, severity, CvdMethod::Machado);
+        let [r, g, b, a] = self.to_rgb().components;
+        let [r, g, b] = daltonize_rgb(&simulation, deficiency, [r, g, b]);
+        Color::from_array([r, g, b, a], ColorModel::RGBA).convert_color(self.color_type)
+    }
+}
+
+//Interleaved sRGB RGBA buffers, alpha is left alone
+pub fn simulate_cvd_rgba(pixels: &mut [f32], deficiency: Deficiency, severity: f32, method: CvdMethod) {
+    let simulation = Simulation::new(deficiency, severity, method);
+    for pixel in pixels.chunks_exact_mut(4) {
+        let [r, g, b] = simulation.apply([pixel[0], pixel[1], pixel[2]]);
+        pixel[..3].copy_from_slice(&[r, g, b]);
+    }
+}
+
+pub fn simulate_cvd_rgba8(pixels: &mut [u8], deficiency: Deficiency, severity: f32, method: CvdMethod) {
+    let simulation = Simulation::new(deficiency, severity, method);
+    for pixel in pixels.chunks_exact_mut(4) {
+        let rgb = [pixel[0], pixel[1], pixel[2]].map(|c| c as f32 / 255.);
+        let rgb = simulation.apply(rgb).map(|c| (c.clamp(0., 1.) * 255.).round() as u8);
+        pixel[..3].copy_from_slice(&rgb);
+    }
+}
+
+pub fn daltonize_rgba(pixels: &mut [f32], deficiency: Deficiency, severity: f32) {
+    let simulation = Simulation::new(deficiency, severity, CvdMethod::Machado);
+    for pixel in pixels.chunks_exact_mut(4) {
+        let [r, g, b] = daltonize_rgb(&simulation, deficiency, [pixel[0], pixel[1], pixel[2]]);
+        pixel[..3].copy_from_slice(&[r, g, b]);
+    }
+}
+
+fn daltonize_rgb(simulation: &Simulation, deficiency: Deficiency, rgb: [f32; 3]) -> [f32; 3] {
+    let shift = match deficiency {
+        Deficiency::Protanopia | Deficiency::Deuteranopia => DALTONIZE_RED_GREEN,
+        Deficiency::Tritanopia => DALTONIZE_BLUE_YELLOW,
+        //Nothing is left to shift the error into
+        Deficiency::Achromatopsia => return rgb,
+    };
+    let linear = rgb.map(srgb_to_linear);
+    let simulated = simulation.apply(rgb).map(srgb_to_linear);
+    let error = [0, 1, 2].map(|i| linear[i] - simulated[i]);
+    let correction = matrix_transform(&shift, error);
+    [0, 1, 2].map(|i| linear_to_srgb((linear[i] + correction[i]).clamp(0., 1.)))
+}
+
+//Picks count colors from the named constants, see cvd_safe_palette_from
+pub fn cvd_safe_palette(count: usize) -> Vec<Color> {
+    let candidates: Vec<Color> = QUATERNARY_COLORS[1..].iter().flatten().copied().collect();
+    cvd_safe_palette_from(&candidates, count)
+}
+
+//Greedy max-min selection: each pick is the candidate whose smallest CIEDE2000 distance to the
+//colors already chosen is largest, where the distance is the worst case over normal vision and
+//full protanopia, deuteranopia and tritanopia. Starts from the most distant pair.
+pub fn cvd_safe_palette_from(candidates: &[Color], count: usize) -> Vec<Color> {
+    let count = count.min(candidates.len());
+    if count == 0 {
+        return Vec::new();
+    }
+    let views: Vec<[[f32; 3]; 4]> = candidates
+        .iter()
+        .map(|color| {
+            [
+                color.to_lab(),
+                color.simulate_cvd(Deficiency::Protanopia, 1.).to_lab(),
+                color.simulate_cvd(Deficiency::Deuteranopia, 1.).to_lab(),
+                color.simulate_cvd(Deficiency::Tritanopia, 1.).to_lab(),
+            ]
+        })
+        .collect();
+    let distance = |i: usize, j: usize| {
+        (0..4)
+            .map(|v| delta_e_2000(views[i][v], views[j][v]))
+            .fold(f32::INFINITY, f32::min)
+    };
+
+    let mut chosen = vec![0];
+    if count > 1 {
+        //Starts below any distance so two different candidates are taken even when all coincide
+        let mut best = (f32::NEG_INFINITY, 0, 1);
+        for i in 0..candidates.len() {
+            for j in i + 1..candidates.len() {
+                let d = distance(i, j);
+                if d > best.0 {
+                    best = (d, i, j);
+                }
+            }
+        }
+        chosen = vec![best.1, best.2];
+    }
+    let mut nearest: Vec<f32> = (0..candidates.len())
+        .map(|i| chosen.iter().map(|&c| distance(i, c)).fold(f32::INFINITY, f32::min))
+        .collect();
+    while chosen.len() < count {
+        let next = (0..candidates.len())
+            .filter(|i| !chosen.contains(i))
+            .max_by(|a, b| nearest[*a].total_cmp(&nearest[*b]))
+            .unwrap();
+        chosen.push(next);
+        for (i, value) in nearest.iter_mut().enumerate() {
+            *value = value.min(distance(i, next));
+        }
+    }
+    chosen.into_iter().map(|i| candidates[i]).collect()
+}
+
+//Everything that only depends on the deficiency, resolved once per buffer
+enum Simulation {
+    Matrix(Matrix, f32),
+    //Two half-plane projections split by a plane through the neutral axis
+    Brettel(Matrix, Matrix, [f32; 3], f32),
+    Achromatic(f32),
+}
+
+impl Simulation {
+    fn new(deficiency: Deficiency, severity: f32, method: CvdMethod) -> Simulation {
+        let severity = severity.clamp(0., 1.);
+        match (deficiency, method) {
+            (Deficiency::Achromatopsia, _) => Simulation::Achromatic(severity),
+            (Deficiency::Protanopia, CvdMethod::Machado) => Simulation::Matrix(machado(&MACHADO_PROTAN, severity), 1.),
+            (Deficiency::Deuteranopia, CvdMethod::Machado) => Simulation::Matrix(machado(&MACHADO_DEUTAN, severity), 1.),
+            (Deficiency::Tritanopia, CvdMethod::Machado) => Simulation::Matrix(machado(&MACHADO_TRITAN, severity), 1.),
+            (Deficiency::Protanopia, CvdMethod::BrettelVienot) => Simulation::Matrix(VIENOT_PROTAN, severity),
+            (Deficiency::Deuteranopia, CvdMethod::BrettelVienot) => Simulation::Matrix(VIENOT_DEUTAN, severity),
+            (Deficiency::Tritanopia, CvdMethod::BrettelVienot) => {
+                Simulation::Brettel(BRETTEL_TRITAN_1, BRETTEL_TRITAN_2, BRETTEL_TRITAN_NORMAL, severity)
+            }
+        }
+    }
+
+    fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let linear = rgb.map(srgb_to_linear);
+        let (simulated, severity) = match self {
+            Simulation::Matrix(matrix, severity) => (matrix_transform(matrix, linear), *severity),
+            Simulation::Brettel(first, second, normal, severity) => {
+                let side = linear[0] * normal[0] + linear[1] * normal[1] + linear[2] * normal[2];
+                let matrix = if side >= 0. { first } else { second };
+                (matrix_transform(matrix, linear), *severity)
+            }
+            Simulation::Achromatic(severity) => {
+                let y = 0.2126 * linear[0] + 0.7152 * linear[1] + 0.0722 * linear[2];
+                ([y, y, y], *severity)
+            }
+        };
+        [0, 1, 2].map(|i| linear_to_srgb(lerp(linear[i], simulated[i], severity)))
+    }
+}
+
+//Interpolates between the two nearest tabulated severities
+fn machado(table: &[Matrix; 10], severity: f32) -> Matrix {
+    let position = severity * 10.;
+    let index = (position.floor() as usize).min(9);
+    let lower = if index == 0 { IDENTITY } else { table[index - 1] };
+    let upper = table[index];
+    let t = position - index as f32;
+    let mut matrix = IDENTITY;
+    for (i, row) in matrix.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = lerp(lower[i][j], upper[i][j], t);
+        }
+    }
+    matrix
+}
+
+//Error redistribution for daltonization
+const DALTONIZE_RED_GREEN: Matrix = [[0., 0., 0.], [0.7, 1., 0.], [0.7, 0., 1.]];
+const DALTONIZE_BLUE_YELLOW: Matrix = [[1., 0., 0.7], [0., 1., 0.7], [0., 0., 0.]];
+
+const IDENTITY: Matrix = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+
+//Severities 0.1 to 1.0
+const MACHADO_PROTAN: [Matrix; 10] = [
+    [[0.856167, 0.182038, -0.038205], [0.029342, 0.955115, 0.015544], [-0.002880, -0.001563, 1.004443]],
+    [[0.734766, 0.334872, -0.069637], [0.051840, 0.919198, 0.028963], [-0.004928, -0.004209, 1.009137]],
+    [[0.630323, 0.465641, -0.095964], [0.069181, 0.890046, 0.040773], [-0.006308, -0.007724, 1.014032]],
+    [[0.539009, 0.579343, -0.118352], [0.082546, 0.866121, 0.051332], [-0.007136, -0.011959, 1.019095]],
+    [[0.458064, 0.679578, -0.137642], [0.092785, 0.846313, 0.060902], [-0.007494, -0.016807, 1.024301]],
+    [[0.385450, 0.769005, -0.154455], [0.100526, 0.829802, 0.069673], [-0.007442, -0.022190, 1.029632]],
+    [[0.319627, 0.849633, -0.169261], [0.106241, 0.815969, 0.077790], [-0.007025, -0.028051, 1.035076]],
+    [[0.259411, 0.923008, -0.182420], [0.110296, 0.804340, 0.085364], [-0.006276, -0.034346, 1.040622]],
+    [[0.203876, 0.990338, -0.194214], [0.112975, 0.794542, 0.092483], [-0.005222, -0.041043, 1.046265]],
+    [[0.152286, 1.052583, -0.204868], [0.114503, 0.786281, 0.099216], [-0.003882, -0.048116, 1.051998]],
+];
+
+const MACHADO_DEUTAN: [Matrix; 10] = [
+    [[0.866435, 0.177704, -0.044139], [0.049567, 0.939063, 0.011370], [-0.003453, 0.007233, 0.996220]],
+    [[0.760729, 0.319078, -0.079807], [0.090568, 0.889315, 0.020117], [-0.006027, 0.013325, 0.992702]],
+    [[0.675425, 0.433850, -0.109275], [0.125303, 0.847755, 0.026942], [-0.007950, 0.018572, 0.989378]],
+    [[0.605511, 0.528560, -0.134071], [0.155318, 0.812366, 0.032316], [-0.009376, 0.023176, 0.986200]],
+    [[0.547494, 0.607765, -0.155259], [0.181692, 0.781742, 0.036566], [-0.010410, 0.027275, 0.983136]],
+    [[0.498864, 0.674741, -0.173604], [0.205199, 0.754872, 0.039929], [-0.011131, 0.030969, 0.980162]],
+    [[0.457771, 0.731899, -0.189670], [0.226409, 0.731012, 0.042579], [-0.011595, 0.034333, 0.977261]],
+    [[0.422823, 0.781057, -0.203881], [0.245752, 0.709602, 0.044646], [-0.011843, 0.037423, 0.974421]],
+    [[0.392952, 0.823610, -0.216562], [0.263559, 0.690210, 0.046232], [-0.011910, 0.040281, 0.971630]],
+    [[0.367322, 0.860646, -0.227968], [0.280085, 0.672501, 0.047413], [-0.011820, 0.042940, 0.968881]],
+];
+
+const MACHADO_TRITAN: [Matrix; 10] = [
+    [[0.926670, 0.092514, -0.019184], [0.021191, 0.964503, 0.014306], [0.008437, 0.054813, 0.936750]],
+    [[0.895720, 0.133330, -0.029050], [0.029997, 0.945400, 0.024603], [0.013027, 0.104707, 0.882266]],
+    [[0.905871, 0.127791, -0.033662], [0.026856, 0.941251, 0.031893], [0.013410, 0.148296, 0.838294]],
+    [[0.948035, 0.089490, -0.037526], [0.014364, 0.946792, 0.038844], [0.010853, 0.193991, 0.795156]],
+    [[1.017277, 0.027029, -0.044306], [-0.006113, 0.958479, 0.047634], [0.006379, 0.248708, 0.744913]],
+    [[1.104996, -0.046633, -0.058363], [-0.032137, 0.971635, 0.060503], [0.001336, 0.317922, 0.680742]],
+    [[1.193214, -0.109812, -0.083402], [-0.058496, 0.979410, 0.079086], [-0.002346, 0.403492, 0.598854]],
+    [[1.257728, -0.139648, -0.118081], [-0.078003, 0.975409, 0.102594], [-0.003316, 0.501214, 0.502102]],
+    [[1.278864, -0.125333, -0.153531], [-0.084748, 0.957674, 0.127074], [-0.000989, 0.601151, 0.399838]],
+    [[1.255528, -0.076749, -0.178779], [-0.078411, 0.930809, 0.147602], [0.004733, 0.691367, 0.303900]],
+];
+
+//Projections onto the dichromat plane, expressed directly in linear sRGB
+const VIENOT_PROTAN: Matrix = [[0.11238, 0.88762, 0.], [0.11238, 0.88762, 0.], [0.00401, -0.00401, 1.]];
+const VIENOT_DEUTAN: Matrix = [[0.29275, 0.70725, 0.], [0.29275, 0.70725, 0.], [-0.02234, 0.02234, 1.]];
+const BRETTEL_TRITAN_1: Matrix = [[1.01277, 0.13548, -0.14826], [-0.01243, 0.86812, 0.14431], [0.07589, 0.80500, 0.11911]];
+const BRETTEL_TRITAN_2: Matrix = [[0.93678, 0.18979, -0.12657], [0.06154, 0.81526, 0.12320], [-0.37562, 1.12767, 0.24796]];
+const BRETTEL_TRITAN_NORMAL: [f32; 3] = [0.03901, -0.02788, -0.01113];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{RED, GREEN, QUATERNARY_COLORS};
+    use crate::cie::delta_e_2000;
+
+    #[test]
+    fn test_neutrals_are_preserved() {
+        for table in [&MACHADO_PROTAN, &MACHADO_DEUTAN, &MACHADO_TRITAN] {
+            for matrix in table.iter() {
+                for row in matrix {
+                    assert!((row.iter().sum::<f32>() - 1.).abs() < 1e-4);
+                }
+            }
+        }
+        let grey = Color::rgb(0.5, 0.5, 0.5);
+        for deficiency in [Deficiency::Protanopia, Deficiency::Deuteranopia, Deficiency::Tritanopia, Deficiency::Achromatopsia] {
+            for method in [CvdMethod::Machado, CvdMethod::BrettelVienot] {
+                let simulated = grey.simulate_cvd_with(deficiency, 1., method).to_array();
+                assert!(simulated.iter().take(3).all(|c| (c - 0.5).abs() < 1e-2));
+            }
+        }
+    }
+
+    #[test]
+    fn test_red_green_confusion() {
+        //Red and green collapse onto the yellow-blue plane, red and green channels become equal
+        for color in [RED, GREEN] {
+            for deficiency in [Deficiency::Protanopia, Deficiency::Deuteranopia] {
+                let simulated = color.simulate_cvd_with(deficiency, 1., CvdMethod::BrettelVienot);
+                assert_eq!(simulated.color_type, color.color_type);
+                let [r, g, _, _] = simulated.to_rgb().to_array();
+                assert!((r - g).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_buffer_matches_single_color() {
+        let colors: Vec<Color> = QUATERNARY_COLORS[1].iter().map(|color| color.to_rgb()).collect();
+        let mut pixels: Vec<f32> = colors.iter().flat_map(|color| color.to_array()).collect();
+        simulate_cvd_rgba(&mut pixels, Deficiency::Tritanopia, 0.65, CvdMethod::Machado);
+        for (color, pixel) in colors.iter().zip(pixels.chunks_exact(4)) {
+            assert_eq!(color.simulate_cvd(Deficiency::Tritanopia, 0.65).to_array(), pixel);
+        }
+    }
+
+    #[test]
+    fn test_daltonize_restores_contrast() {
+        let simulated = |color: Color| color.simulate_cvd(Deficiency::Deuteranopia, 1.).to_lab();
+        let before = delta_e_2000(simulated(RED), simulated(GREEN));
+        let (red, green) = (RED.daltonize(Deficiency::Deuteranopia, 1.), GREEN.daltonize(Deficiency::Deuteranopia, 1.));
+        assert!(delta_e_2000(simulated(red), simulated(green)) > before);
+        let grey = Color::rgb(0.5, 0.5, 0.5).daltonize(Deficiency::Protanopia, 1.).to_array();
+        assert!(grey.iter().take(3).all(|c| (c - 0.5).abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_cvd_safe_palette() {
+        let palette = cvd_safe_palette(6);
+        assert_eq!(palette.len(), 6);
+        for (i, a) in palette.iter().enumerate() {
+            for b in palette.iter().skip(i + 1) {
+                for deficiency in [Deficiency::Protanopia, Deficiency::Deuteranopia, Deficiency::Tritanopia] {
+                    let (a, b) = (a.simulate_cvd(deficiency, 1.), b.simulate_cvd(deficiency, 1.));
+                    assert!(a.delta_e(b) > 10.);
+                }
+            }
+        }
+
+        //Only alpha tells these apart, every distance is zero
+        let grey = Color::rgb(0.5, 0.5, 0.5);
+        let candidates = [grey.set_alpha(0.2), grey.set_alpha(0.5), grey.set_alpha(0.8)];
+        for count in 2..=3 {
+            let palette = cvd_safe_palette_from(&candidates, count);
+            for (i, a) in palette.iter().enumerate() {
+                assert!(!palette[i + 1..].contains(a));
+            }
+            assert_eq!(palette.len(), count);
+        }
+    }
+}
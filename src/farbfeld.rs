@@ -0,0 +1,64 @@
+use crate::image::{Image, MAX_PIXELS};
+use crate::lut3d::invalid_data;
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 8] = b"farbfeld";
+
+//16 bit big endian RGBA, sRGB and not premultiplied
+pub fn read_farbfeld(mut reader: impl Read) -> io::Result<Image> {
+    let mut header = [0u8; 16];
+    reader.read_exact(&mut header)?;
+    if &header[..8] != MAGIC {
+        return Err(invalid_data("not a farbfeld file"));
+    }
+    let width = u32::from_be_bytes([header[8], header[9], header[10], header[11]]) as usize;
+    let height = u32::from_be_bytes([header[12], header[13], header[14], header[15]]) as usize;
+    let size = width
+        .checked_mul(height)
+        .filter(|pixels| *pixels <= MAX_PIXELS)
+        .ok_or_else(|| invalid_data("bad farbfeld header"))?
+        * 8;
+    //Grows with the data actually there, so a header claiming more than the file holds allocates nothing extra
+    let mut body = Vec::new();
+    reader.take(size as u64).read_to_end(&mut body)?;
+    if body.len() != size {
+        return Err(invalid_data("farbfeld pixel data ends early"));
+    }
+    let pixels: Vec<u16> = body.chunks_exact(2).map(|b| u16::from_be_bytes([b[0], b[1]])).collect();
+    Ok(Image::from_integers(width, height, &pixels))
+}
+
+impl Image {
+    pub fn write_farbfeld(&self, mut writer: impl Write) -> io::Result<()> {
+        let mut data = Vec::with_capacity(16 + self.pixels.len() * 2);
+        data.extend_from_slice(MAGIC);
+        data.extend((self.width as u32).to_be_bytes());
+        data.extend((self.height as u32).to_be_bytes());
+        data.extend(self.to_rgba16().into_iter().flat_map(u16::to_be_bytes));
+        writer.write_all(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    #[test]
+    fn test_round_trip() {
+        let image = Image::from_grid(&[vec![Color::rgb(1., 0.5, 0.), Color::rgb(0., 0.25, 1.).set_alpha(0.5)]]);
+        let mut data = Vec::new();
+        image.write_farbfeld(&mut data).unwrap();
+        assert_eq!(data.len(), 16 + 2 * 8);
+        assert_eq!(&data[16..18], &[255, 255]);
+        assert_eq!(read_farbfeld(&data[..]).unwrap().to_rgba16(), image.to_rgba16());
+        assert!(read_farbfeld(&data[..20]).is_err());
+
+        //Headers claiming more pixels than the file holds or than can be counted
+        for size in [[0, 0, 0, 2, 0, 0, 0, 1], [0xff; 8], [0, 0x10, 0, 0, 0, 0x10, 0, 0]] {
+            let mut header = MAGIC.to_vec();
+            header.extend(size);
+            assert_eq!(read_farbfeld(&header[..]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{Color, ColorModel};
+use crate::transformations::{linear_to_srgb, matrix_transform, srgb_to_linear};
+
+//Slack for rounding in the spherical conversions
+const GAMUT_EPSILON: f32 = 1e-4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamutMapping {
+    //Clamp every RGB channel on its own, hue can shift
+    Clip,
+    //Lower OKLCh chroma at constant lightness and hue until the color fits
+    ReduceChroma,
+    //CSS Color 4 binary search in OKLCh, stops once the clipped color is within a JND
+    Css4,
+    //Slide along the straight line to the grey of equal spherical luminance
+    ProjectToGrey,
+}
+
+impl Color {
+    //True when the sRGB value lies inside the unit cube
+    pub fn in_gamut(&self) -> bool {
+        let [r, g, b, _] = self.to_rgb().components;
+        [r, g, b].iter().all(|c| (-GAMUT_EPSILON..=1. + GAMUT_EPSILON).contains(c))
+    }
+
+    //The result is returned in the color's own model
+    pub fn map_to_gamut(&self, method: GamutMapping) -> Color {
+        if self.in_gamut() {
+            return *self;
+        }
+        let [r, g, b, a] = self.to_rgb().components;
+        let [r, g, b] = match method {
+            GamutMapping::Clip => clip([r, g, b]),
+            GamutMapping::ReduceChroma => reduce_chroma([r, g, b]),
+            GamutMapping::Css4 => css4_map([r, g, b]),
+            GamutMapping::ProjectToGrey => project_to_grey([r, g, b]),
+        };
+        Color::from_array([r, g, b, a], ColorModel::RGBA).convert_color(self.color_type)
+    }
+}
+
+fn clip(rgb: [f32; 3]) -> [f32; 3] {
+    rgb.map(|c| c.clamp(0., 1.))
+}
+
+fn fits(rgb: [f32; 3]) -> bool {
+    rgb.iter().all(|c| (-GAMUT_EPSILON..=1. + GAMUT_EPSILON).contains(c))
+}
+
+fn reduce_chroma(rgb: [f32; 3]) -> [f32; 3] {
+    let [l, c, h] = oklab_to_oklch(srgb_to_oklab(rgb));
+    if l >= 1. {
+        return [1., 1., 1.];
+    }
+    if l <= 0. {
+        return [0., 0., 0.];
+    }
+    let (mut low, mut high) = (0., c);
+    while high - low > GAMUT_EPSILON {
+        let mid = (low + high) / 2.;
+        if fits(oklab_to_srgb(oklch_to_oklab([l, mid, h]))) {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    clip(oklab_to_srgb(oklch_to_oklab([l, low, h])))
+}
+
+fn css4_map(rgb: [f32; 3]) -> [f32; 3] {
+    const JND: f32 = 0.02;
+    let origin = oklab_to_oklch(srgb_to_oklab(rgb));
+    let [l, c, h] = origin;
+    if l >= 1. {
+        return [1., 1., 1.];
+    }
+    if l <= 0. {
+        return [0., 0., 0.];
+    }
+    let delta_e_ok = |lch: [f32; 3], clipped: [f32; 3]| {
+        let (a, b) = (oklch_to_oklab(lch), srgb_to_oklab(clipped));
+        ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+    };
+    let mut clipped = clip(rgb);
+    if delta_e_ok(origin, clipped) < JND {
+        return clipped;
+    }
+    let (mut min, mut max) = (0., c);
+    let mut min_in_gamut = true;
+    while max - min > GAMUT_EPSILON {
+        let chroma = (min + max) / 2.;
+        let current = [l, chroma, h];
+        let current_rgb = oklab_to_srgb(oklch_to_oklab(current));
+        if min_in_gamut && fits(current_rgb) {
+            min = chroma;
+            continue;
+        }
+        clipped = clip(current_rgb);
+        let error = delta_e_ok(current, clipped);
+        if error < JND {
+            if JND - error < GAMUT_EPSILON {
+                return clipped;
+            }
+            min_in_gamut = false;
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+    clipped
+}
+
+//The grey point keeps the vector length of the color, the luminance of spherical HCL
+fn project_to_grey(rgb: [f32; 3]) -> [f32; 3] {
+    let length = (rgb[0] * rgb[0] + rgb[1] * rgb[1] + rgb[2] * rgb[2]).sqrt();
+    let grey = (length / 3f32.sqrt()).clamp(0., 1.);
+    let t = rgb.iter().fold(1f32, |t, &value| {
+        if value > 1. {
+            t.min((1. - grey) / (value - grey))
+        } else if value < 0. {
+            t.min(grey / (grey - value))
+        } else {
+            t
+        }
+    });
+    clip(rgb.map(|value| grey + t * (value - grey)))
+}
+
+const LINEAR_SRGB_TO_LMS: [[f32; 3]; 3] = [
+    [0.41222147, 0.53633254, 0.05144599],
+    [0.2119035, 0.6806995, 0.10739696],
+    [0.08830246, 0.28171884, 0.62997866],
+];
+const LMS_TO_OKLAB: [[f32; 3]; 3] = [
+    [0.21045426, 0.7936178, -0.00407205],
+    [1.9779985, -2.4285922, 0.4505937],
+    [0.02590404, 0.78277177, -0.80867577],
+];
+const OKLAB_TO_LMS: [[f32; 3]; 3] = [
+    [1., 0.39633778, 0.21580376],
+    [1., -0.10556135, -0.06385417],
+    [1., -0.08948418, -1.2914855],
+];
+const LMS_TO_LINEAR_SRGB: [[f32; 3]; 3] = [
+    [4.0767417, -3.3077116, 0.23096993],
+    [-1.268438, 2.6097574, -0.3413194],
+    [-0.00419609, -0.7034186, 1.7076147],
+];
+
+//OKLab of an sRGB encoded triple
+pub fn srgb_to_oklab(rgb: [f32; 3]) -> [f32; 3] {
+    let linear = rgb.map(|c| c.signum() * srgb_to_linear(c.abs()));
+    let lms = matrix_transform(&LINEAR_SRGB_TO_LMS, linear);
+    matrix_transform(&LMS_TO_OKLAB, lms.map(f32::cbrt))
+}
+
+pub fn oklab_to_srgb(lab: [f32; 3]) -> [f32; 3] {
+    let lms = matrix_transform(&OKLAB_TO_LMS, lab).map(|c| c * c * c);
+    matrix_transform(&LMS_TO_LINEAR_SRGB, lms).map(|c| {
+        //The sRGB curve is mirrored for negative values so out of gamut colors round trip
+        c.signum() * linear_to_srgb(c.abs())
+    })
+}
+
+pub fn oklab_to_oklch(lab: [f32; 3]) -> [f32; 3] {
+    let [l, a, b] = lab;
+    [l, (a * a + b * b).sqrt(), b.atan2(a)]
+}
+
+pub fn oklch_to_oklab(lch: [f32; 3]) -> [f32; 3] {
+    let [l, c, h] = lch;
+    [l, c * h.cos(), c * h.sin()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_method_lands_in_gamut() {
+        let colors = [
+            Color::spherical_hcl(0.1, 0.8, 1.6),
+            Color::from_array([0.9, 0.4, -0.3, 1.], ColorModel::YUVA),
+            Color::rgb(1.3, -0.2, 0.5),
+        ];
+        for color in colors {
+            assert!(!color.in_gamut());
+            for method in [GamutMapping::Clip, GamutMapping::ReduceChroma, GamutMapping::Css4, GamutMapping::ProjectToGrey] {
+                let mapped = color.map_to_gamut(method);
+                assert_eq!(mapped.color_type, color.color_type);
+                assert!(mapped.to_rgb().in_gamut(), "{:?} {:?}", method, mapped);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reduce_chroma_keeps_hue_and_lightness() {
+        let rgb = [1.2, 0.3, -0.1];
+        let before = oklab_to_oklch(srgb_to_oklab(rgb));
+        let after = oklab_to_oklch(srgb_to_oklab(reduce_chroma(rgb)));
+        assert!((before[0] - after[0]).abs() < 1e-3);
+        assert!((before[2] - after[2]).abs() < 1e-2);
+        assert!(after[1] < before[1]);
+        let in_gamut = Color::rgb(0.2, 0.4, 0.6);
+        assert_eq!(in_gamut.map_to_gamut(GamutMapping::Css4), in_gamut);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::lut3d::{Interpolation, Lut3D};
+use num_traits::{AsPrimitive, PrimInt, Unsigned};
+
+//Hald CLUT images store a level² cube as a square image level³ pixels wide.
+//Pixels run in reading order with red changing fastest, then green, then blue, the same order as .cube.
+
+pub fn hald_width(level: usize) -> usize {
+    level * level * level
+}
+
+//The level of a square Hald image with this many pixels
+pub fn hald_level(pixel_count: usize) -> Option<usize> {
+    (2..=16).find(|level| hald_width(*level) * hald_width(*level) == pixel_count)
+}
+
+//Interleaved RGBA identity image, opaque, to be graded in an image editor
+pub fn hald_identity<T>(level: usize) -> Vec<T>
+where
+    T: Unsigned + PrimInt + AsPrimitive<f32> + 'static,
+    f32: AsPrimitive<T>,
+{
+    Lut3D::identity(level * level).to_hald(level, Interpolation::Tetrahedral)
+}
+
+impl Lut3D {
+    //Reads an interleaved RGBA Hald image, None when the pixel count is not a square Hald image
+    pub fn from_hald<T>(pixels: &[T]) -> Option<Lut3D>
+    where
+        T: Unsigned + PrimInt + AsPrimitive<f32> + 'static,
+    {
+        if !pixels.len().is_multiple_of(4) {
+            return None;
+        }
+        let level = hald_level(pixels.len() / 4)?;
+        let scale: f32 = T::max_value().as_();
+        let table = pixels.chunks_exact(4).map(|pixel| [0, 1, 2].map(|i| pixel[i].as_() / scale)).collect();
+        Some(Lut3D { title: None, size: level * level, table, domain_min: [0.; 3], domain_max: [1.; 3], shaper: None })
+    }
+
+    //Samples the LUT, shaper and domain included, into an interleaved RGBA Hald image
+    pub fn to_hald<T>(&self, level: usize, interpolation: Interpolation) -> Vec<T>
+    where
+        T: Unsigned + PrimInt + AsPrimitive<f32> + 'static,
+        f32: AsPrimitive<T>,
+    {
+        let size = level * level;
+        let last = (size - 1) as f32;
+        let scale: f32 = T::max_value().as_();
+        let mut pixels = Vec::with_capacity(size * size * size * 4);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    let node = [r, g, b].map(|i| i as f32 / last);
+                    let input = [0, 1, 2].map(|c| self.domain_min[c] + node[c] * (self.domain_max[c] - self.domain_min[c]));
+                    let rgb = self.apply(input, interpolation);
+                    pixels.extend(rgb.map(|c| (c.clamp(0., 1.) * scale).round().as_()));
+                    pixels.push(T::max_value());
+                }
+            }
+        }
+        pixels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    #[test]
+    fn test_hald_round_trip() {
+        let identity: Vec<u8> = hald_identity(4);
+        assert_eq!(identity.len(), 64 * 64 * 4);
+        assert_eq!(&identity[..8], &[0, 0, 0, 255, 17, 0, 0, 255]);
+
+        //Grade the image as an editor would, then read it back as a LUT
+        let grade = |color: Color| color.gamma_transform(1.4);
+        let mut graded = identity.clone();
+        for pixel in graded.chunks_exact_mut(4) {
+            let color = Color::rgb(pixel[0] as f32 / 255., pixel[1] as f32 / 255., pixel[2] as f32 / 255.);
+            pixel.copy_from_slice(&grade(color).to_integers::<u8>(Some(255)));
+        }
+        let lut = Lut3D::from_hald(&graded).unwrap();
+        assert_eq!(lut.size, 16);
+        let applied = lut.apply([0.3, 0.6, 0.9], Interpolation::Tetrahedral);
+        let expected = grade(Color::rgb(0.3, 0.6, 0.9)).to_array();
+        assert!(applied.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-2), "{:?}", applied);
+        assert_eq!(lut.to_hald::<u8>(4, Interpolation::Trilinear), graded);
+        assert!(Lut3D::from_hald(&[0u16; 4 * 100]).is_none());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{Color, ColorModel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Harmony {
+    Complementary,
+    SplitComplementary,
+    Triadic,
+    //Rectangle, two complementary pairs 60 degrees apart
+    Tetradic,
+    Square,
+    Analogous,
+    //Same hue, darker steps of the base luminance
+    Monochromatic,
+}
+
+impl Harmony {
+    //Hue offsets in rotations, the base color is always first
+    pub fn hue_offsets(&self) -> Vec<f32> {
+        match self {
+            Harmony::Complementary => vec![0., 1. / 2.],
+            Harmony::SplitComplementary => vec![0., 5. / 12., 7. / 12.],
+            Harmony::Triadic => vec![0., 1. / 3., 2. / 3.],
+            Harmony::Tetradic => vec![0., 1. / 6., 1. / 2., 2. / 3.],
+            Harmony::Square => vec![0., 1. / 4., 1. / 2., 3. / 4.],
+            Harmony::Analogous => vec![0., 1. / 12., -1. / 12.],
+            Harmony::Monochromatic => vec![0., 0., 0., 0.],
+        }
+    }
+}
+
+impl Color {
+    pub fn harmony(&self, harmony: Harmony) -> Vec<Color> {
+        self.harmony_in(harmony, ColorModel::SphericalHCLA)
+    }
+
+    //Rotates the hue in the given cylindrical model, leaving the other components untouched,
+    //and returns every color in the model of the base color.
+    //Non-cylindrical models fall back to spherical HCL.
+    pub fn harmony_in(&self, harmony: Harmony, model: ColorModel) -> Vec<Color> {
+        let model = if model.is_cylindrical() { model } else { ColorModel::SphericalHCLA };
+        let base = self.convert_color(model);
+        harmony
+            .hue_offsets()
+            .iter()
+            .enumerate()
+            .map(|(i, offset)| {
+                let mut components = base.components;
+                components[0] = (components[0] + offset).rem_euclid(1.);
+                if harmony == Harmony::Monochromatic {
+                    components = scale_lightness(components, model, 1. - i as f32 / 4.);
+                }
+                Color { components, color_type: model }.convert_color(self.color_type)
+            })
+            .collect()
+    }
+
+    pub fn complementary(&self) -> Vec<Color> {
+        self.harmony(Harmony::Complementary)
+    }
+
+    pub fn split_complementary(&self) -> Vec<Color> {
+        self.harmony(Harmony::SplitComplementary)
+    }
+
+    pub fn triadic(&self) -> Vec<Color> {
+        self.harmony(Harmony::Triadic)
+    }
+
+    pub fn tetradic(&self) -> Vec<Color> {
+        self.harmony(Harmony::Tetradic)
+    }
+
+    pub fn square(&self) -> Vec<Color> {
+        self.harmony(Harmony::Square)
+    }
+
+    pub fn analogous(&self) -> Vec<Color> {
+        self.harmony(Harmony::Analogous)
+    }
+
+    pub fn monochromatic(&self) -> Vec<Color> {
+        self.harmony(Harmony::Monochromatic)
+    }
+}
+
+//HWB models store blackness in the last axis, so darkening grows it instead of shrinking it
+fn scale_lightness(components: [f32; 4], model: ColorModel, factor: f32) -> [f32; 4] {
+    let [h, a, b, alpha] = components;
+    match model {
+        ColorModel::SphericalHWBA | ColorModel::CubicHWBA => [h, a * factor, 1. - (1. - b) * factor, alpha],
+        _ => [h, a, b * factor, alpha],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Color, ColorModel};
+    use super::Harmony;
+
+    fn assert_close(a: Color, b: Color) {
+        for (x, y) in a.to_array().iter().zip(b.to_array().iter()) {
+            assert!((x - y).abs() < 1e-3, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_triadic_red() {
+        let colors = Color::rgb(1., 0., 0.).triadic();
+        assert_eq!(colors.len(), 3);
+        assert_close(colors[0], Color::rgb(1., 0., 0.));
+        assert_close(colors[1], Color::rgb(0., 1., 0.));
+        assert_close(colors[2], Color::rgb(0., 0., 1.));
+    }
+
+    #[test]
+    fn test_harmony_keeps_model_and_chroma() {
+        let base = Color::spherical_hcl(0.1, 0.6, 0.8);
+        for harmony in [Harmony::Complementary, Harmony::Square, Harmony::Analogous] {
+            for color in base.harmony_in(harmony, ColorModel::SphericalHCLA) {
+                assert_eq!(color.color_type, ColorModel::SphericalHCLA);
+                assert!((color.components[1] - 0.6).abs() < 1e-6);
+                assert!((color.components[2] - 0.8).abs() < 1e-6);
+            }
+        }
+    }
+}
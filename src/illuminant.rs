@@ -0,0 +1,150 @@
+use crate::observer::{wavelength, Observer, SAMPLE_COUNT};
+use crate::spectrum::Spectrum;
+use crate::temperature::{daylight_xy, planck};
+
+//CIE daylight basis functions, 380nm to 780nm in 10nm steps
+const DAYLIGHT_S0: [f32; SAMPLE_COUNT] = [
+    63.4, 65.8, 94.8, 104.8, 105.9, 96.8, 113.9, 125.6, 125.5, 121.3,
+    121.3, 113.5, 113.1, 110.8, 106.5, 108.8, 105.3, 104.4, 100.0, 96.0,
+    95.1, 89.1, 90.5, 90.3, 88.4, 84.0, 85.1, 81.9, 82.6, 84.9,
+    81.3, 71.9, 74.3, 76.4, 63.3, 71.7, 77.0, 65.2, 47.7, 68.6,
+    65.0,
+];
+const DAYLIGHT_S1: [f32; SAMPLE_COUNT] = [
+    38.5, 35.0, 43.4, 46.3, 43.9, 37.1, 36.7, 35.9, 32.6, 27.9,
+    24.3, 20.1, 16.2, 13.2, 8.6, 6.1, 4.2, 1.9, 0.0, -1.6,
+    -3.5, -3.5, -5.8, -7.2, -8.6, -9.5, -10.9, -10.7, -12.0, -14.0,
+    -13.6, -12.0, -13.3, -12.9, -10.6, -11.6, -12.2, -10.2, -7.8, -11.2,
+    -10.4,
+];
+const DAYLIGHT_S2: [f32; SAMPLE_COUNT] = [
+    3.0, 1.2, -1.1, -0.5, -0.7, -1.2, -2.6, -2.9, -2.8, -2.6,
+    -2.6, -1.8, -1.5, -1.3, -1.2, -1.0, -0.5, -0.3, 0.0, 0.2,
+    0.5, 2.1, 3.2, 4.1, 4.7, 5.1, 6.7, 7.3, 8.6, 9.8,
+    10.2, 8.3, 9.6, 8.5, 7.0, 7.6, 8.0, 6.7, 5.2, 7.4,
+    6.8,
+];
+
+//CIE tabulated D65 and D50, 380nm to 780nm in 10nm steps
+const D65_TABLE: [f32; SAMPLE_COUNT] = [
+    49.9755, 54.6482, 82.7549, 91.486, 93.4318, 86.6823, 104.865, 117.008, 117.812, 114.861,
+    115.923, 108.811, 109.354, 107.802, 104.79, 107.689, 104.405, 104.046, 100.0, 96.3342,
+    95.788, 88.6856, 90.0062, 89.5991, 87.6987, 83.2886, 83.6992, 80.0268, 80.2146, 82.2778,
+    78.2842, 69.7213, 71.6091, 74.349, 61.604, 69.8856, 75.087, 63.5927, 46.4182, 66.8054,
+    63.3828,
+];
+const D50_TABLE: [f32; SAMPLE_COUNT] = [
+    24.4875, 29.871, 49.308, 56.513, 60.034, 57.818, 74.825, 87.247, 90.612, 91.368,
+    95.109, 91.963, 95.724, 96.613, 97.129, 102.099, 100.755, 102.317, 100.0, 97.735,
+    98.918, 93.499, 97.688, 99.269, 99.042, 95.722, 98.857, 95.667, 98.19, 103.003,
+    99.133, 87.381, 91.604, 92.889, 76.854, 86.511, 92.58, 78.225, 57.692, 82.923,
+    78.274,
+];
+
+//CIE fluorescent F2 (cool white), F7 (broadband daylight) and F11 (narrow band tri-phosphor),
+//380nm to 780nm in 5nm steps
+const FINE_COUNT: usize = 2 * SAMPLE_COUNT - 1;
+const F2_TABLE: [f32; FINE_COUNT] = [
+    1.18, 1.48, 1.84, 2.15, 3.44, 15.69, 3.85, 3.74, 4.19, 4.62,
+    5.06, 34.98, 11.81, 6.27, 6.63, 6.93, 7.19, 7.4, 7.54, 7.62,
+    7.65, 7.62, 7.62, 7.45, 7.28, 7.15, 7.05, 7.04, 7.16, 7.47,
+    8.04, 8.88, 10.01, 24.88, 16.64, 14.59, 16.16, 17.56, 18.62, 21.47,
+    22.79, 19.29, 18.66, 17.73, 16.54, 15.21, 13.8, 12.36, 10.95, 9.65,
+    8.4, 7.32, 6.31, 5.43, 4.68, 4.02, 3.45, 2.96, 2.55, 2.19,
+    1.89, 1.64, 1.53, 1.27, 1.1, 0.99, 0.88, 0.76, 0.68, 0.61,
+    0.56, 0.54, 0.51, 0.47, 0.47, 0.43, 0.46, 0.47, 0.4, 0.33,
+    0.27,
+];
+const F7_TABLE: [f32; FINE_COUNT] = [
+    2.56, 3.18, 3.84, 4.53, 6.15, 19.37, 7.37, 7.05, 7.71, 8.41,
+    9.15, 44.14, 17.52, 11.35, 12.0, 12.58, 13.08, 13.45, 13.71, 13.88,
+    13.95, 13.93, 13.82, 13.64, 13.43, 13.25, 13.08, 12.93, 12.78, 12.6,
+    12.44, 12.33, 12.26, 29.52, 17.05, 12.44, 12.58, 12.72, 12.83, 15.46,
+    16.75, 12.83, 12.67, 12.45, 12.19, 11.89, 11.6, 11.35, 11.12, 10.95,
+    10.76, 10.42, 10.11, 10.04, 10.02, 10.11, 9.87, 8.65, 7.27, 6.44,
+    5.83, 5.41, 5.04, 4.57, 4.12, 3.77, 3.46, 3.08, 2.73, 2.47,
+    2.25, 2.06, 1.9, 1.75, 1.62, 1.54, 1.45, 1.32, 1.17, 0.99,
+    0.81,
+];
+const F11_TABLE: [f32; FINE_COUNT] = [
+    0.91, 0.63, 0.46, 0.37, 1.29, 12.68, 1.59, 1.79, 2.46, 3.33,
+    4.49, 33.94, 12.13, 6.95, 7.19, 7.12, 6.72, 6.13, 5.46, 4.79,
+    5.66, 14.29, 14.96, 8.97, 4.72, 2.33, 1.47, 1.1, 0.89, 0.83,
+    1.18, 4.9, 39.59, 72.84, 32.61, 7.52, 2.83, 1.96, 1.67, 4.43,
+    11.28, 14.76, 12.73, 9.74, 7.33, 9.72, 55.27, 42.58, 13.18, 13.16,
+    12.26, 5.11, 2.07, 2.34, 3.58, 3.01, 2.48, 2.14, 1.54, 1.33,
+    1.46, 1.94, 2.0, 1.2, 1.35, 4.1, 5.58, 2.51, 0.57, 0.27,
+    0.23, 0.21, 0.24, 0.24, 0.2, 0.24, 0.32, 0.26, 0.16, 0.12,
+    0.09,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Illuminant {
+    //Incandescent, a 2856K blackbody
+    A,
+    D50,
+    D55,
+    D65,
+    D75,
+    //Equal energy
+    E,
+    //Fluorescent lamps, see the tables above
+    F2,
+    F7,
+    F11,
+    //CIE daylight at any correlated color temperature from 4000K to 25000K
+    Daylight(f32),
+    Blackbody(f32),
+}
+
+impl Illuminant {
+    //Relative spectral power, 100 at 560nm except for the F series, which keep the CIE tables' scale
+    pub fn spectrum(&self) -> Spectrum {
+        let values: Vec<f32> = match self {
+            Illuminant::A => blackbody(2856.),
+            Illuminant::Blackbody(kelvin) => blackbody(*kelvin),
+            Illuminant::D50 => D50_TABLE.to_vec(),
+            Illuminant::D65 => D65_TABLE.to_vec(),
+            //The other D series temperatures follow the 1968 revision of the radiation constant
+            Illuminant::D55 => daylight(5503.),
+            Illuminant::D75 => daylight(7504.),
+            Illuminant::Daylight(kelvin) => daylight(*kelvin),
+            Illuminant::E => vec![100.; SAMPLE_COUNT],
+            Illuminant::F2 => fold_fine(&F2_TABLE),
+            Illuminant::F7 => fold_fine(&F7_TABLE),
+            Illuminant::F11 => fold_fine(&F11_TABLE),
+        };
+        Spectrum::new((0..SAMPLE_COUNT).map(wavelength).collect(), values)
+    }
+
+    //XYZ of a perfect white reflector, Y = 1
+    pub fn white(&self, observer: Observer) -> [f32; 3] {
+        Spectrum::constant(1.).to_xyz(observer, *self)
+    }
+}
+
+fn blackbody(kelvin: f32) -> Vec<f32> {
+    (0..SAMPLE_COUNT).map(|i| 100. * planck(wavelength(i), kelvin)).collect()
+}
+
+//The mercury lines at 405nm, 435nm and 545nm sit between the 10nm samples, so the 5nm table is
+//folded with 1/4, 1/2, 1/4 weights instead of picking every other value, which keeps their power
+fn fold_fine(table: &[f32; FINE_COUNT]) -> Vec<f32> {
+    (0..SAMPLE_COUNT)
+        .map(|i| {
+            let center = 2 * i;
+            let (below, above) = (center.saturating_sub(1), (center + 1).min(FINE_COUNT - 1));
+            0.25 * table[below] + 0.5 * table[center] + 0.25 * table[above]
+        })
+        .collect()
+}
+
+fn daylight(kelvin: f32) -> Vec<f32> {
+    let [x, y] = daylight_xy(kelvin);
+    let m = 0.0241 + 0.2562 * x - 0.7341 * y;
+    let m1 = (-1.3515 - 1.7703 * x + 5.9114 * y) / m;
+    let m2 = (0.03 - 31.4424 * x + 30.0717 * y) / m;
+    (0..SAMPLE_COUNT)
+        .map(|i| DAYLIGHT_S0[i] + m1 * DAYLIGHT_S1[i] + m2 * DAYLIGHT_S2[i])
+        .collect()
+}
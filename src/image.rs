@@ -0,0 +1,106 @@
+use crate::{Color, ColorModel};
+use num_traits::{AsPrimitive, PrimInt, Unsigned};
+
+//Readers refuse headers claiming more pixels than this before allocating anything
+pub(crate) const MAX_PIXELS: usize = 400_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitDepth {
+    Eight,
+    Sixteen,
+}
+
+//A flat image of interleaved RGBA in 0 to 1, rows top to bottom
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<f32>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, pixels: Vec<f32>) -> Image {
+        assert_eq!(pixels.len(), width * height * 4, "pixels must hold width * height RGBA values");
+        Image { width, height, pixels }
+    }
+
+    //Rows of equal length, such as bilinear_gradient returns. Colors are stored as RGBA.
+    pub fn from_grid(grid: &[Vec<Color>]) -> Image {
+        let width = grid.first().map_or(0, Vec::len);
+        assert!(grid.iter().all(|row| row.len() == width), "every row must have the same length");
+        let pixels = grid.iter().flatten().flat_map(|color| color.to_rgb().to_array()).collect();
+        Image::new(width, grid.len(), pixels)
+    }
+
+    pub fn to_grid(&self) -> Vec<Vec<Color>> {
+        self.pixels
+            .chunks_exact(4 * self.width.max(1))
+            .map(|row| row.chunks_exact(4).map(|p| Color::from_array([p[0], p[1], p[2], p[3]], ColorModel::RGBA)).collect())
+            .collect()
+    }
+
+    //Each color of the grid drawn as a square block of `size` pixels, e.g. for QUATERNARY_COLORS
+    pub fn swatch_sheet(grid: &[Vec<Color>], size: usize) -> Image {
+        let colors = Image::from_grid(grid);
+        let (width, height) = (colors.width * size, colors.height * size);
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.extend(colors.pixel(x / size, y / size).to_array());
+            }
+        }
+        Image::new(width, height, pixels)
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        let start = (y * self.width + x) * 4;
+        let p = &self.pixels[start..start + 4];
+        Color::from_array([p[0], p[1], p[2], p[3]], ColorModel::RGBA)
+    }
+
+    pub fn from_integers<T>(width: usize, height: usize, pixels: &[T]) -> Image
+    where
+        T: Unsigned + PrimInt + AsPrimitive<f32> + 'static,
+    {
+        let scale: f32 = T::max_value().as_();
+        Image::new(width, height, pixels.iter().map(|v| v.as_() / scale).collect())
+    }
+
+    //Out of range components saturate
+    pub fn to_integers<T>(&self) -> Vec<T>
+    where
+        T: Unsigned + PrimInt + AsPrimitive<f32> + 'static,
+        f32: AsPrimitive<T>,
+    {
+        let scale: f32 = T::max_value().as_();
+        self.pixels.iter().map(|v| (v.clamp(0., 1.) * scale).round().as_()).collect()
+    }
+
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.to_integers()
+    }
+
+    pub fn to_rgba16(&self) -> Vec<u16> {
+        self.to_integers()
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        self.pixels.chunks_exact(4).all(|p| p[3] >= 1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bilinear_gradient;
+
+    #[test]
+    fn test_grid_round_trip() {
+        let grid = bilinear_gradient(&Color::rgb(1., 0., 0.), &Color::rgb(0., 1., 0.), &Color::rgb(0., 0., 1.), &Color::rgb(1., 1., 1.), 3, 4);
+        let image = Image::from_grid(&grid);
+        assert_eq!((image.width, image.height), (4, 3));
+        assert_eq!(image.pixel(3, 2), grid[2][3].to_rgb());
+        assert_eq!(image.to_grid().len(), 3);
+        assert_eq!(Image::from_integers(4, 3, &image.to_rgba16()).to_rgba8(), image.to_rgba8());
+    }
+}
@@ -3,12 +3,42 @@
 
 pub mod constants;
 pub mod transformations;
+pub mod harmony;
+pub mod tones;
+pub mod theme;
+pub mod contrast;
+pub mod cvd;
+pub mod cie;
+pub mod gamut;
+pub mod rgb_space;
+pub mod adaptation;
+pub mod observer;
+pub mod temperature;
+pub mod illuminant;
+pub mod spectrum;
+pub mod upsampling;
+pub mod pigment;
+pub mod metamerism;
+pub mod batch;
+pub mod soa;
+pub mod parallel;
+pub mod lut;
+pub mod lut3d;
+pub mod hald;
+pub mod lut1d;
+pub mod image;
+pub mod netpbm;
+pub mod farbfeld;
+pub mod qoi;
+pub mod bmp;
+pub mod png;
 
 use num_traits::{AsPrimitive, PrimInt, Unsigned};
 use transformations::{*};
 use std::f32::consts::PI;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ColorModel {
     //Spectral Color
     // WBIS,
@@ -75,6 +105,7 @@ pub enum ColorSpace{
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     components: [f32; 4], //maybe make a fixed point library in the future
     color_type: ColorModel,
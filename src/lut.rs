@@ -0,0 +1,204 @@
+use crate::ColorModel;
+use crate::batch::Conversion;
+
+//A model to model conversion of RGBA8 pixels through a 3D table over the first three components,
+//built once and interpolated trilinearly. Nodes sit on exact byte values. Cells the conversion is
+//not smooth across, like the hue seam, hue and saturation around the grey axis and black, or the
+//HSL saturation seam at lightness 0.5, are found while building and use the exact path.
+//
+//Only RGBA and CMYA sources get a table. With the default step of 5, no output component is off
+//from convert_rgba8 by more than 2 levels, into any model, on a sample that crosses every value of
+//each channel with a coarse grid of the other two. The table pays off for the trig heavy spherical
+//targets, the cheap linear models are as fast through convert_rgba8.
+//
+//Every other source always uses the exact path. Cylindrical sources wrap hue and lose it at zero
+//chroma, which trilinear interpolation can't follow, and CMYK and RGBW have four color components.
+pub struct ConversionLut {
+    conversion: Conversion,
+    to: ColorModel,
+    size: usize,
+    //Cell index and position inside the cell for every byte value
+    split: [(usize, f32); 256],
+    table: Option<Table>,
+}
+
+struct Table {
+    nodes: Vec<[f32; 4]>,
+    exact_cells: Vec<bool>,
+}
+
+//Largest interpolation error at a probe, in 0 to 1 units, before a cell falls back to the exact path
+const CELL_TOLERANCE: f32 = 1. / 255.;
+
+impl ConversionLut {
+    pub fn new(from: ColorModel, to: ColorModel) -> ConversionLut {
+        ConversionLut::with_step(from, to, 5)
+    }
+
+    //The step between nodes in byte values must divide 255, smaller steps are more accurate and larger
+    pub fn with_step(from: ColorModel, to: ColorModel, step: usize) -> ConversionLut {
+        assert!(step > 0 && 255 % step == 0, "the step must divide 255");
+        let size = 255 / step + 1;
+        let split = std::array::from_fn(|value| {
+            let index = (value / step).min(size - 2);
+            (index, (value - index * step) as f32 / step as f32)
+        });
+        let mut lut = ConversionLut { conversion: Conversion::new(from, to), to, size, split, table: None };
+        if !matches!(from, ColorModel::RGBA | ColorModel::CMYA) {
+            return lut;
+        }
+        let mut nodes = Vec::with_capacity(size * size * size);
+        for r in 0..size {
+            for g in 0..size {
+                for b in 0..size {
+                    nodes.push(lut.exact([r, g, b].map(|i| (i * step) as f32)));
+                }
+            }
+        }
+        let mut table = Table { nodes, exact_cells: Vec::new() };
+        let cells = size - 1;
+        let probes = [[0.5, 0.5, 0.5], [0., 0.5, 0.5], [1., 0.5, 0.5], [0.5, 0., 0.5], [0.5, 1., 0.5], [0.5, 0.5, 0.], [0.5, 0.5, 1.]];
+        for r in 0..cells {
+            for g in 0..cells {
+                for b in 0..cells {
+                    let smooth = probes.iter().all(|t| {
+                        let position = [0, 1, 2].map(|i| ([r, g, b][i] * step) as f32 + t[i] * step as f32);
+                        let (exact, interpolated) = (lut.exact(position), lut.interpolate(&table, [r, g, b], *t));
+                        ConversionLut::difference(exact, interpolated) <= CELL_TOLERANCE
+                    });
+                    table.exact_cells.push(!smooth);
+                }
+            }
+        }
+        lut.table = Some(table);
+        lut
+    }
+
+    //Byte valued RGB, full alpha
+    fn exact(&self, rgb: [f32; 3]) -> [f32; 4] {
+        let [r, g, b] = rgb.map(|c| c / 255.);
+        //NaN, as CMYK gives for black, is stored as 0 like the byte encoding would
+        self.conversion.apply([r, g, b, 1.]).map(|c| if c.is_nan() { 0. } else { c })
+    }
+
+    fn difference(a: [f32; 4], b: [f32; 4]) -> f32 {
+        (0..4).fold(0f32, |worst, i| worst.max((a[i] - b[i]).abs()))
+    }
+
+    fn interpolate(&self, table: &Table, cell: [usize; 3], t: [f32; 3]) -> [f32; 4] {
+        let [r, g, b] = cell;
+        let [tr, tg, tb] = t;
+        let (stride_r, stride_g) = (self.size * self.size, self.size);
+        let base = r * stride_r + g * stride_g + b;
+        let nodes = &table.nodes[base..=base + stride_r + stride_g + 1];
+        let lerp = |a: [f32; 4], b: [f32; 4], t: f32| [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t);
+        let along_b = |offset: usize| lerp(nodes[offset], nodes[offset + 1], tb);
+        let low = lerp(along_b(0), along_b(stride_g), tg);
+        let high = lerp(along_b(stride_r), along_b(stride_r + stride_g), tg);
+        lerp(low, high, tr)
+    }
+
+    pub fn lookup(&self, pixel: [u8; 4]) -> [f32; 4] {
+        let alpha = pixel[3] as f32 / 255.;
+        let Some(table) = &self.table else {
+            return self.conversion.apply(pixel.map(|c| c as f32 / 255.));
+        };
+        let [(r, tr), (g, tg), (b, tb)] = [pixel[0], pixel[1], pixel[2]].map(|c| self.split[c as usize]);
+        let cells = self.size - 1;
+        let mut result = if table.exact_cells[(r * cells + g) * cells + b] {
+            self.exact([pixel[0], pixel[1], pixel[2]].map(|c| c as f32))
+        } else {
+            self.interpolate(table, [r, g, b], [tr, tg, tb])
+        };
+        if !has_fourth_color_component(self.to) {
+            result[3] = alpha;
+        }
+        result
+    }
+
+    //Same output encoding as convert_rgba8, components outside 0 to 1 saturate
+    pub fn apply(&self, input: &[u8], output: &mut [u8]) {
+        assert_eq!(input.len(), output.len(), "input and output must be the same length");
+        assert_eq!(input.len() % 4, 0, "pixels have four components");
+        for (source, destination) in input.chunks_exact(4).zip(output.chunks_exact_mut(4)) {
+            let pixel = self.lookup([source[0], source[1], source[2], source[3]]);
+            for (value, component) in destination.iter_mut().zip(pixel) {
+                *value = (component.clamp(0., 1.) * 255.).round() as u8;
+            }
+        }
+    }
+}
+
+fn has_fourth_color_component(model: ColorModel) -> bool {
+    matches!(model, ColorModel::CMYK | ColorModel::RGBW)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch::convert_rgba8;
+
+    const MODELS: [ColorModel; 10] = [
+        ColorModel::RGBA,
+        ColorModel::CMYA,
+        ColorModel::RGBW,
+        ColorModel::CMYK,
+        ColorModel::SphericalHCLA,
+        ColorModel::SphericalHWBA,
+        ColorModel::CubicHSLA,
+        ColorModel::CubicHSVA,
+        ColorModel::CubicHWBA,
+        ColorModel::YUVA,
+    ];
+
+    //Every r, g and b value appears, crossed with a coarser grid on the other two, alpha passed through
+    fn stratified_input() -> Vec<u8> {
+        let coarse: Vec<u8> = (7..=255).step_by(31).collect();
+        let mut input = Vec::new();
+        for value in 0..=255u8 {
+            for a in &coarse {
+                for b in &coarse {
+                    for pixel in [[value, *a, *b], [*a, value, *b], [*a, *b, value]] {
+                        input.extend_from_slice(&[pixel[0], pixel[1], pixel[2], value ^ b]);
+                    }
+                }
+            }
+        }
+        input
+    }
+
+    fn worst_error(input: &[u8], from: ColorModel, to: ColorModel) -> u8 {
+        let lut = ConversionLut::new(from, to);
+        let (mut exact, mut fast) = (vec![0; input.len()], vec![0; input.len()]);
+        convert_rgba8(input, from, to, &mut exact);
+        lut.apply(input, &mut fast);
+        exact.iter().zip(fast.iter()).map(|(a, b)| a.abs_diff(*b)).max().unwrap()
+    }
+
+    fn assert_table_within_documented_error(from: ColorModel) {
+        let input = stratified_input();
+        for to in MODELS {
+            let worst = worst_error(&input, from, to);
+            assert!(worst <= 2, "{:?} to {:?} off by {}", from, to, worst);
+        }
+    }
+
+    #[test]
+    fn test_rgba_within_documented_error() {
+        assert_table_within_documented_error(ColorModel::RGBA);
+    }
+
+    #[test]
+    fn test_cmya_within_documented_error() {
+        assert_table_within_documented_error(ColorModel::CMYA);
+    }
+
+    #[test]
+    fn test_other_sources_are_exact() {
+        let input = stratified_input();
+        for from in MODELS.into_iter().skip(2) {
+            assert!(ConversionLut::new(from, ColorModel::RGBA).table.is_none());
+            assert_eq!(worst_error(&input, from, ColorModel::SphericalHCLA), 0, "{:?}", from);
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use crate::Color;
+use crate::lut3d::invalid_data;
+use crate::transformations::DefinedColor;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+//Per channel curves sampled evenly over an input domain
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lut1D {
+    pub domain_min: f32,
+    pub domain_max: f32,
+    pub channels: [Vec<f32>; 3],
+}
+
+impl Lut1D {
+    pub fn new(channels: [Vec<f32>; 3]) -> Lut1D {
+        assert!(channels.iter().all(|c| c.len() >= 2), "every channel needs at least two samples");
+        Lut1D { domain_min: 0., domain_max: 1., channels }
+    }
+
+    pub fn len(&self) -> usize {
+        self.channels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn apply_channel(&self, channel: usize, value: f32) -> f32 {
+        let samples = &self.channels[channel];
+        let t = ((value - self.domain_min) / (self.domain_max - self.domain_min)).clamp(0., 1.);
+        let position = t * (samples.len() - 1) as f32;
+        let index = (position as usize).min(samples.len() - 2);
+        let t = position - index as f32;
+        samples[index] + (samples[index + 1] - samples[index]) * t
+    }
+
+    pub fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        [0, 1, 2].map(|channel| self.apply_channel(channel, rgb[channel]))
+    }
+
+    //Resampled onto 0 to 1 when the domain is different, alpha is left linear
+    pub fn to_defined_color(&self, color: Color) -> DefinedColor {
+        let samples = [0, 1, 2].map(|channel| {
+            if self.domain_min == 0. && self.domain_max == 1. {
+                self.channels[channel].clone()
+            } else {
+                let last = (self.len() - 1) as f32;
+                (0..self.len()).map(|i| self.apply_channel(channel, i as f32 / last)).collect()
+            }
+        });
+        let [r, g, b] = samples;
+        DefinedColor::sampled(color, [r, g, b, vec![0., 1.]])
+    }
+
+    //Samples the red, green and blue curves of a DefinedColor
+    pub fn from_defined_color(curves: &DefinedColor, length: usize) -> Lut1D {
+        let [r, g, b, _] = curves.sample_curves(length);
+        Lut1D::new([r, g, b])
+    }
+
+    //Picks the format from the extension: .spi1d, .lut or .csp
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Lut1D> {
+        let text = fs::read_to_string(&path)?;
+        match extension(path.as_ref()).as_str() {
+            "spi1d" => Lut1D::from_spi1d(&text),
+            "lut" => Lut1D::from_discreet(&text),
+            "csp" => Lut1D::from_csp(&text),
+            other => Err(invalid_data(format!("unknown 1D LUT extension {:?}", other))),
+        }
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = match extension(path.as_ref()).as_str() {
+            "spi1d" => self.to_spi1d(),
+            "lut" => self.to_discreet(),
+            "csp" => self.to_csp(),
+            other => return Err(invalid_data(format!("unknown 1D LUT extension {:?}", other))),
+        };
+        fs::write(path, text)
+    }
+
+    //Sony Imageworks .spi1d with one or three components
+    pub fn from_spi1d(text: &str) -> io::Result<Lut1D> {
+        let (mut domain_min, mut domain_max) = (0., 1.);
+        let mut components = 1;
+        let mut rows: Vec<Vec<f32>> = Vec::new();
+        let mut in_data = false;
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let mut words = line.split_whitespace();
+            match words.next().unwrap_or_default() {
+                "{" => in_data = true,
+                "}" => in_data = false,
+                "From" => [domain_min, domain_max] = parse_numbers(words)?.try_into().map_err(|_| invalid_data("From needs two numbers"))?,
+                "Components" => components = parse_numbers(words)?.first().copied().unwrap_or(1.) as usize,
+                _ if in_data => rows.push(parse_numbers(line.split_whitespace())?),
+                _ => {}
+            }
+        }
+        if !(components == 1 || components == 3) || rows.iter().any(|row| row.len() != components) {
+            return Err(invalid_data("expected one or three components on every line"));
+        }
+        let channels = [0, 1, 2].map(|channel| rows.iter().map(|row| row[channel.min(components - 1)]).collect::<Vec<f32>>());
+        check_length(&channels)?;
+        Ok(Lut1D { domain_min, domain_max, channels })
+    }
+
+    pub fn to_spi1d(&self) -> String {
+        let mut text = String::from("Version 1\n");
+        let _ = writeln!(text, "From {:.6} {:.6}", self.domain_min, self.domain_max);
+        let _ = writeln!(text, "Length {}\nComponents 3\n{{", self.len());
+        for i in 0..self.len() {
+            let _ = writeln!(text, "    {:.6} {:.6} {:.6}", self.channels[0][i], self.channels[1][i], self.channels[2][i]);
+        }
+        text.push_str("}\n");
+        text
+    }
+
+    //Autodesk Discreet .lut: a `LUT: tables length [depth]` header, then integer code values
+    //one per line, table after table. Without a depth the output has the bit depth of the length.
+    pub fn from_discreet(text: &str) -> io::Result<Lut1D> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+        let header: Vec<&str> = lines.next().unwrap_or_default().split_whitespace().collect();
+        let (tables, length, depth) = match header[..] {
+            ["LUT:", tables, length, ref rest @ ..] => {
+                let parse = |word: &str| word.trim_end_matches('f').parse::<usize>().map_err(|_| invalid_data("bad LUT: header"));
+                let length = parse(length)?;
+                if length < 2 {
+                    return Err(invalid_data("a 1D LUT needs at least two entries"));
+                }
+                let depth = match rest.first() {
+                    Some(depth) => parse(depth)?,
+                    None => length.ilog2() as usize,
+                };
+                (parse(tables)?, length, depth)
+            }
+            _ => return Err(invalid_data("missing LUT: header")),
+        };
+        if !(tables == 1 || tables == 3 || tables == 4) {
+            return Err(invalid_data("expected 1, 3 or 4 tables"));
+        }
+        if !(1..=32).contains(&depth) {
+            return Err(invalid_data("expected a bit depth from 1 to 32"));
+        }
+        let values = parse_numbers(lines.flat_map(str::split_whitespace))?;
+        if Some(values.len()) != tables.checked_mul(length) {
+            return Err(invalid_data(format!("expected {} tables of {} values, found {}", tables, length, values.len())));
+        }
+        let scale = ((1u64 << depth) - 1) as f32;
+        let channels = [0, 1, 2].map(|channel| {
+            let table = channel.min(tables - 1);
+            values[table * length..(table + 1) * length].iter().map(|v| v / scale).collect::<Vec<f32>>()
+        });
+        check_length(&channels)?;
+        Ok(Lut1D { domain_min: 0., domain_max: 1., channels })
+    }
+
+    //Written at the bit depth of the length, so lengths that are not a power of two are resampled to 1024.
+    //The format has no domain, so one other than 0 to 1 is baked into the samples.
+    pub fn to_discreet(&self) -> String {
+        let length = if self.len().is_power_of_two() { self.len() } else { 1024 };
+        let scale = (length - 1) as f32;
+        let mut text = format!("LUT: 3 {}\n", length);
+        for channel in 0..3 {
+            for i in 0..length {
+                let value = self.apply_channel(channel, self.domain_min + (self.domain_max - self.domain_min) * i as f32 / scale);
+                let _ = writeln!(text, "{}", (value.clamp(0., 1.) * scale).round() as u32);
+            }
+        }
+        text
+    }
+
+    //Rising Sun cineSpace .csp 1D: three prelut shapers, then the table.
+    //The shapers are folded into the curves, which are resampled over the shapers' input range.
+    pub fn from_csp(text: &str) -> io::Result<Lut1D> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next() != Some("CSPLUTV100") || lines.next() != Some("1D") {
+            return Err(invalid_data("expected a CSPLUTV100 1D header"));
+        }
+        let mut in_metadata = false;
+        let mut lines = lines.filter(|line| {
+            let skip = in_metadata || *line == "BEGIN METADATA";
+            in_metadata = (in_metadata || *line == "BEGIN METADATA") && *line != "END METADATA";
+            !skip
+        });
+        let mut next_numbers = || -> io::Result<Vec<f32>> {
+            parse_numbers(lines.next().ok_or_else(|| invalid_data("file ends early"))?.split_whitespace())
+        };
+        let mut prelut = Vec::new();
+        for _ in 0..3 {
+            let count = next_numbers()?.first().copied().unwrap_or(0.) as usize;
+            let (inputs, outputs) = (next_numbers()?, next_numbers()?);
+            if count < 2 || inputs.len() != count || outputs.len() != count {
+                return Err(invalid_data("bad prelut"));
+            }
+            prelut.push((inputs, outputs));
+        }
+        let length = next_numbers()?.first().copied().unwrap_or(0.) as usize;
+        let mut rows = Vec::new();
+        for _ in 0..length {
+            let row = next_numbers()?;
+            if row.len() != 3 {
+                return Err(invalid_data("expected three values per line"));
+            }
+            rows.push(row);
+        }
+        let table = [0, 1, 2].map(|channel| rows.iter().map(|row| row[channel]).collect());
+        check_length(&table)?;
+        let table = Lut1D::new(table);
+        let domain_min = prelut.iter().map(|(inputs, _)| inputs[0]).fold(f32::INFINITY, f32::min);
+        let domain_max = prelut.iter().map(|(inputs, _)| inputs[inputs.len() - 1]).fold(f32::NEG_INFINITY, f32::max);
+        let last = (length - 1) as f32;
+        let channels = [0, 1, 2].map(|channel| {
+            let (inputs, outputs) = &prelut[channel];
+            (0..length)
+                .map(|i| {
+                    let x = domain_min + (domain_max - domain_min) * i as f32 / last;
+                    table.apply_channel(channel, piecewise_linear(inputs, outputs, x))
+                })
+                .collect()
+        });
+        Ok(Lut1D { domain_min, domain_max, channels })
+    }
+
+    pub fn to_csp(&self) -> String {
+        let mut text = String::from("CSPLUTV100\n1D\n\n");
+        for _ in 0..3 {
+            let _ = writeln!(text, "2\n{:.6} {:.6}\n0.0 1.0", self.domain_min, self.domain_max);
+        }
+        let _ = writeln!(text, "\n{}", self.len());
+        for i in 0..self.len() {
+            let _ = writeln!(text, "{:.6} {:.6} {:.6}", self.channels[0][i], self.channels[1][i], self.channels[2][i]);
+        }
+        text
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase()
+}
+
+fn parse_numbers<'a>(words: impl Iterator<Item = &'a str>) -> io::Result<Vec<f32>> {
+    words.map(|word| word.parse::<f32>().map_err(|_| invalid_data(format!("expected a number, found {:?}", word)))).collect()
+}
+
+fn check_length(channels: &[Vec<f32>; 3]) -> io::Result<()> {
+    if channels[0].len() < 2 {
+        return Err(invalid_data("a 1D LUT needs at least two entries"));
+    }
+    Ok(())
+}
+
+//Interpolates through ascending breakpoints, holding the ends
+fn piecewise_linear(inputs: &[f32], outputs: &[f32], x: f32) -> f32 {
+    let upper = inputs.partition_point(|input| *input < x).clamp(1, inputs.len() - 1);
+    let (x0, x1) = (inputs[upper - 1], inputs[upper]);
+    let t = ((x - x0) / (x1 - x0)).clamp(0., 1.);
+    outputs[upper - 1] + (outputs[upper] - outputs[upper - 1]) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_round_trip_defined_color() {
+        let curves = DefinedColor::component_gamma(Color::rgb(0.5, 0.5, 0.5), [2.2, 1.8, 1., 1.]);
+        let lut = Lut1D::from_defined_color(&curves, 1024);
+        for parsed in [Lut1D::from_spi1d(&lut.to_spi1d()), Lut1D::from_discreet(&lut.to_discreet()), Lut1D::from_csp(&lut.to_csp())] {
+            let loaded = parsed.unwrap().to_defined_color(Color::rgb(0.3, 0.6, 0.9)).collapse_color().to_array();
+            let expected = DefinedColor::component_gamma(Color::rgb(0.3, 0.6, 0.9), [2.2, 1.8, 1., 1.]).collapse_color().to_array();
+            for (a, b) in loaded.iter().zip(expected.iter()) {
+                assert!((a - b).abs() < 2e-3, "{:?} {:?}", loaded, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_other_tools() {
+        let spi = "Version 1\nFrom 0.0 2.0\nLength 3\nComponents 1\n{\n 0.0\n 0.25\n 1.0\n}\n";
+        let lut = Lut1D::from_spi1d(spi).unwrap();
+        assert_eq!(lut.apply([1., 1.5, 2.]), [0.25, 0.625, 1.]);
+
+        let discreet = "LUT: 1 4\n0\n1\n2\n3\n";
+        assert_eq!(Lut1D::from_discreet(discreet).unwrap().channels[2], vec![0., 1. / 3., 2. / 3., 1.]);
+
+        //The prelut squeezes 0 to 4 into the first half of the table
+        let csp = "CSPLUTV100\n1D\n\nBEGIN METADATA\nwritten by hand\nEND METADATA\n\
+            2\n0 4\n0 0.5\n2\n0 4\n0 0.5\n2\n0 4\n0 0.5\n\n3\n0 0 0\n0.2 0.2 0.2\n1 1 1\n";
+        let lut = Lut1D::from_csp(csp).unwrap();
+        assert_eq!((lut.domain_min, lut.domain_max), (0., 4.));
+        assert!((lut.apply([2., 4., 0.])[0] - 0.1).abs() < 1e-6);
+        assert!(Lut1D::from_discreet("LUT: 3 4\n0\n").is_err());
+    }
+
+    #[test]
+    fn test_malformed_headers() {
+        for discreet in ["LUT: 3 0", "LUT: 3 1\n0\n0\n0\n", "LUT: 1 2 70\n0\n1\n", "LUT: 1 2 0\n0\n1\n", "LUT: 4 9223372036854775807\n"] {
+            assert!(Lut1D::from_discreet(discreet).is_err(), "{}", discreet);
+        }
+        let prelut = "CSPLUTV100\n1D\n2\n0 1\n0 1\n2\n0 1\n0 1\n2\n0 1\n0 1\n";
+        for table in ["0\n", "1\n0 0 0\n", "4000000000\n0 0 0\n"] {
+            assert!(Lut1D::from_csp(&format!("{}{}", prelut, table)).is_err(), "{}", table);
+        }
+    }
+}
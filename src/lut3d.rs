@@ -0,0 +1,301 @@
+use crate::{Color, ColorModel};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+//Largest sizes the .cube specification allows
+const MAX_3D_SIZE: usize = 256;
+const MAX_1D_SIZE: usize = 65536;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Trilinear,
+    //Splits each cell into six tetrahedra, keeps the grey axis exact and is what most video tools use
+    Tetrahedral,
+}
+
+//A per channel 1D table applied before the cube, as written by Resolve next to LUT_3D_SIZE
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shaper {
+    pub table: Vec<[f32; 3]>,
+    pub domain_min: [f32; 3],
+    pub domain_max: [f32; 3],
+}
+
+impl Shaper {
+    pub fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let last = (self.table.len() - 1) as f32;
+        [0, 1, 2].map(|channel| {
+            let position = normalize(rgb[channel], self.domain_min[channel], self.domain_max[channel]) * last;
+            let index = (position as usize).min(self.table.len() - 2);
+            let t = position - index as f32;
+            let (a, b) = (self.table[index][channel], self.table[index + 1][channel]);
+            a + (b - a) * t
+        })
+    }
+}
+
+//A 3D color lookup table over RGB. Entries are stored with red changing fastest, as in .cube files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lut3D {
+    pub title: Option<String>,
+    pub size: usize,
+    pub table: Vec<[f32; 3]>,
+    pub domain_min: [f32; 3],
+    pub domain_max: [f32; 3],
+    pub shaper: Option<Shaper>,
+}
+
+fn normalize(value: f32, min: f32, max: f32) -> f32 {
+    ((value - min) / (max - min)).clamp(0., 1.)
+}
+
+pub(crate) fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+impl Lut3D {
+    pub fn identity(size: usize) -> Lut3D {
+        Lut3D::from_fn(size, |color| color)
+    }
+
+    //Samples a color transform at every node of a size³ cube over 0 to 1 RGB
+    pub fn from_fn(size: usize, transform: impl Fn(Color) -> Color) -> Lut3D {
+        assert!(size >= 2, "a cube needs at least two nodes per side");
+        let last = (size - 1) as f32;
+        let mut table = Vec::with_capacity(size * size * size);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    let [r, g, b, _] = transform(Color::rgb(r as f32 / last, g as f32 / last, b as f32 / last)).to_rgb().components;
+                    table.push([r, g, b]);
+                }
+            }
+        }
+        Lut3D { title: None, size, table, domain_min: [0.; 3], domain_max: [1.; 3], shaper: None }
+    }
+
+    fn node(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        self.table[r + self.size * (g + self.size * b)]
+    }
+
+    pub fn apply(&self, rgb: [f32; 3], interpolation: Interpolation) -> [f32; 3] {
+        let rgb = match &self.shaper {
+            Some(shaper) => shaper.apply(rgb),
+            None => rgb,
+        };
+        let last = (self.size - 1) as f32;
+        let mut cell = [0; 3];
+        let mut t = [0.; 3];
+        for channel in 0..3 {
+            let position = normalize(rgb[channel], self.domain_min[channel], self.domain_max[channel]) * last;
+            cell[channel] = (position as usize).min(self.size - 2);
+            t[channel] = position - cell[channel] as f32;
+        }
+        let [r, g, b] = cell;
+        let corner = |dr: usize, dg: usize, db: usize| self.node(r + dr, g + dg, b + db);
+        let mix = |weights: [(f32, [f32; 3]); 4]| {
+            [0, 1, 2].map(|i| weights.iter().map(|(weight, node)| weight * node[i]).sum())
+        };
+        let [tr, tg, tb] = t;
+        match interpolation {
+            Interpolation::Trilinear => {
+                let lerp = |a: [f32; 3], b: [f32; 3], t: f32| [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t);
+                let along_r = |dg: usize, db: usize| lerp(corner(0, dg, db), corner(1, dg, db), tr);
+                let low = lerp(along_r(0, 0), along_r(1, 0), tg);
+                let high = lerp(along_r(0, 1), along_r(1, 1), tg);
+                lerp(low, high, tb)
+            }
+            Interpolation::Tetrahedral => {
+                let (c000, c111) = (corner(0, 0, 0), corner(1, 1, 1));
+                if tr >= tg && tg >= tb {
+                    mix([(1. - tr, c000), (tr - tg, corner(1, 0, 0)), (tg - tb, corner(1, 1, 0)), (tb, c111)])
+                } else if tr >= tb && tb >= tg {
+                    mix([(1. - tr, c000), (tr - tb, corner(1, 0, 0)), (tb - tg, corner(1, 0, 1)), (tg, c111)])
+                } else if tb >= tr && tr >= tg {
+                    mix([(1. - tb, c000), (tb - tr, corner(0, 0, 1)), (tr - tg, corner(1, 0, 1)), (tg, c111)])
+                } else if tg >= tr && tr >= tb {
+                    mix([(1. - tg, c000), (tg - tr, corner(0, 1, 0)), (tr - tb, corner(1, 1, 0)), (tb, c111)])
+                } else if tg >= tb && tb >= tr {
+                    mix([(1. - tg, c000), (tg - tb, corner(0, 1, 0)), (tb - tr, corner(0, 1, 1)), (tr, c111)])
+                } else {
+                    mix([(1. - tb, c000), (tb - tg, corner(0, 0, 1)), (tg - tr, corner(0, 1, 1)), (tr, c111)])
+                }
+            }
+        }
+    }
+
+    //Interleaved RGBA, alpha is left alone
+    pub fn apply_to_slice(&self, pixels: &mut [f32], interpolation: Interpolation) {
+        for pixel in pixels.chunks_exact_mut(4) {
+            let [r, g, b] = self.apply([pixel[0], pixel[1], pixel[2]], interpolation);
+            pixel[..3].copy_from_slice(&[r, g, b]);
+        }
+    }
+
+    pub fn apply_to_rgba8(&self, pixels: &mut [u8], interpolation: Interpolation) {
+        for pixel in pixels.chunks_exact_mut(4) {
+            let rgb = self.apply([0, 1, 2].map(|i| pixel[i] as f32 / 255.), interpolation);
+            for (value, component) in pixel.iter_mut().zip(rgb) {
+                *value = (component.clamp(0., 1.) * 255.).round() as u8;
+            }
+        }
+    }
+
+    //Adobe .cube, with the Resolve LUT_1D_SIZE shaper section
+    pub fn from_cube(text: &str) -> io::Result<Lut3D> {
+        let mut title = None;
+        let (mut size, mut shaper_size) = (None, None);
+        let (mut domain_min, mut domain_max) = ([0.; 3], [1.; 3]);
+        let (mut shaper_min, mut shaper_max) = ([0.; 3], [1.; 3]);
+        let mut rows: Vec<[f32; 3]> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str| invalid_data(format!("line {}: {}", number + 1, message));
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+            let numbers = |words: std::str::SplitWhitespace| {
+                words.map(|word| word.parse::<f32>().map_err(|_| error("expected a number"))).collect::<io::Result<Vec<f32>>>()
+            };
+            let triple = |values: Vec<f32>| -> io::Result<[f32; 3]> {
+                values.try_into().map_err(|_| error("expected three numbers"))
+            };
+            let range = |values: Vec<f32>| -> io::Result<([f32; 3], [f32; 3])> {
+                match values[..] {
+                    [min, max] => Ok(([min; 3], [max; 3])),
+                    _ => Err(error("expected a minimum and maximum")),
+                }
+            };
+            let count = |values: Vec<f32>, max: usize| match values[..] {
+                [count] if count >= 2. && count <= max as f32 && count.fract() == 0. => Ok(count as usize),
+                _ => Err(error(&format!("expected a size from 2 to {}", max))),
+            };
+            match keyword {
+                "TITLE" => title = Some(line["TITLE".len()..].trim().trim_matches('"').to_string()),
+                "LUT_3D_SIZE" => size = Some(count(numbers(words)?, MAX_3D_SIZE)?),
+                "LUT_1D_SIZE" => shaper_size = Some(count(numbers(words)?, MAX_1D_SIZE)?),
+                "DOMAIN_MIN" => domain_min = triple(numbers(words)?)?,
+                "DOMAIN_MAX" => domain_max = triple(numbers(words)?)?,
+                "LUT_3D_INPUT_RANGE" => (domain_min, domain_max) = range(numbers(words)?)?,
+                "LUT_1D_INPUT_RANGE" => (shaper_min, shaper_max) = range(numbers(words)?)?,
+                _ if keyword.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.') => {
+                    rows.push(triple(numbers(line.split_whitespace())?)?);
+                }
+                //Vendor keywords are skipped like comments
+                _ => {}
+            }
+        }
+        let size = size.ok_or_else(|| invalid_data("missing LUT_3D_SIZE, 1D only files are not 3D LUTs"))?;
+        let shaper_rows = shaper_size.unwrap_or(0);
+        let expected = size
+            .checked_mul(size)
+            .and_then(|area| area.checked_mul(size))
+            .and_then(|volume| volume.checked_add(shaper_rows))
+            .ok_or_else(|| invalid_data("LUT size is too large"))?;
+        if rows.len() != expected {
+            return Err(invalid_data(format!("expected {} entries, found {}", expected, rows.len())));
+        }
+        let table = rows.split_off(shaper_rows);
+        let shaper = shaper_size.map(|_| Shaper { table: rows, domain_min: shaper_min, domain_max: shaper_max });
+        Ok(Lut3D { title, size, table, domain_min, domain_max, shaper })
+    }
+
+    pub fn to_cube(&self) -> String {
+        let mut text = String::new();
+        if let Some(title) = &self.title {
+            let _ = writeln!(text, "TITLE \"{}\"", title);
+        }
+        let triple = |values: &[f32; 3]| format!("{:.6} {:.6} {:.6}", values[0], values[1], values[2]);
+        if let Some(shaper) = &self.shaper {
+            //Resolve only understands a single input range for every channel
+            let _ = writeln!(text, "LUT_1D_SIZE {}", shaper.table.len());
+            let _ = writeln!(text, "LUT_1D_INPUT_RANGE {:.6} {:.6}", shaper.domain_min[0], shaper.domain_max[0]);
+            let _ = writeln!(text, "LUT_3D_SIZE {}", self.size);
+            let _ = writeln!(text, "LUT_3D_INPUT_RANGE {:.6} {:.6}", self.domain_min[0], self.domain_max[0]);
+            for row in &shaper.table {
+                let _ = writeln!(text, "{}", triple(row));
+            }
+        } else {
+            let _ = writeln!(text, "LUT_3D_SIZE {}", self.size);
+            let _ = writeln!(text, "DOMAIN_MIN {}", triple(&self.domain_min));
+            let _ = writeln!(text, "DOMAIN_MAX {}", triple(&self.domain_max));
+        }
+        for row in &self.table {
+            let _ = writeln!(text, "{}", triple(row));
+        }
+        text
+    }
+
+    pub fn read_cube(path: impl AsRef<Path>) -> io::Result<Lut3D> {
+        Lut3D::from_cube(&fs::read_to_string(path)?)
+    }
+
+    pub fn write_cube(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_cube())
+    }
+}
+
+impl Color {
+    //The LUT works on the color's RGB, the result is returned in the color's own model
+    pub fn apply_lut(&self, lut: &Lut3D, interpolation: Interpolation) -> Color {
+        let [r, g, b, a] = self.to_rgb().components;
+        let [r, g, b] = lut.apply([r, g, b], interpolation);
+        Color::from_array([r, g, b, a], ColorModel::RGBA).convert_color(self.color_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sampled_grade_round_trips_through_cube() {
+        let grade = |color: Color| color.remap_rgb_components(0.5, 1., 0.9, 0.8).gamma_transform(1.2);
+        let lut = Lut3D::from_fn(17, grade);
+        let parsed = Lut3D::from_cube(&lut.to_cube()).unwrap();
+        assert_eq!(parsed.size, 17);
+        let color = Color::rgb(0.3, 0.55, 0.8);
+        let expected = grade(color).to_rgb().to_array();
+        for interpolation in [Interpolation::Trilinear, Interpolation::Tetrahedral] {
+            let applied = color.apply_lut(&parsed, interpolation).to_array();
+            for (a, b) in applied.iter().zip(expected.iter()) {
+                assert!((a - b).abs() < 5e-3, "{:?} {:?} {:?}", interpolation, applied, expected);
+            }
+        }
+        //Identity tables are exact at any point
+        let identity = Lut3D::identity(2);
+        for interpolation in [Interpolation::Trilinear, Interpolation::Tetrahedral] {
+            let applied = identity.apply([0.2, 0.7, 0.4], interpolation);
+            assert!(applied.iter().zip([0.2, 0.7, 0.4]).all(|(a, b)| (a - b).abs() < 1e-6));
+        }
+    }
+
+    #[test]
+    fn test_parse_shaper_and_domain() {
+        let text = "# Resolve style\nTITLE \"log\"\nLUT_1D_SIZE 2\nLUT_1D_INPUT_RANGE 0 2\nLUT_3D_SIZE 2\n\
+            0 0 0\n1 1 1\n\
+            0 0 0\n1 0 0\n0 1 0\n1 1 0\n0 0 1\n1 0 1\n0 1 1\n1 1 1\n";
+        let lut = Lut3D::from_cube(text).unwrap();
+        assert_eq!(lut.title.as_deref(), Some("log"));
+        //Halved by the shaper's 0 to 2 input range
+        assert_eq!(lut.apply([1., 2., 0.5], Interpolation::Tetrahedral), [0.5, 1., 0.25]);
+        let reparsed = Lut3D::from_cube(&lut.to_cube()).unwrap();
+        assert_eq!(reparsed, lut);
+
+        let domain = "LUT_3D_SIZE 2\nDOMAIN_MIN -1 -1 -1\nDOMAIN_MAX 1 1 1\n0 0 0\n1 0 0\n0 1 0\n1 1 0\n0 0 1\n1 0 1\n0 1 1\n1 1 1\n";
+        assert_eq!(Lut3D::from_cube(domain).unwrap().apply([0., 1., -1.], Interpolation::Trilinear), [0.5, 1., 0.]);
+        assert!(Lut3D::from_cube("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+    }
+
+    #[test]
+    fn test_malformed_sizes() {
+        for size in ["LUT_3D_SIZE 3000000", "LUT_3D_SIZE 257", "LUT_3D_SIZE 1e30", "LUT_3D_SIZE 2.5", "LUT_3D_SIZE 2\nLUT_1D_SIZE 65537"] {
+            let error = Lut3D::from_cube(&format!("{}\n0 0 0\n", size)).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", size);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::cie::{delta_e_2000, xyz_to_lab};
+use crate::illuminant::Illuminant;
+use crate::observer::Observer;
+use crate::spectrum::Spectrum;
+
+impl Spectrum {
+    //CIELAB of the reflectance under the illuminant, relative to the illuminant's own white
+    pub fn to_lab(&self, observer: Observer, illuminant: Illuminant) -> [f32; 3] {
+        xyz_to_lab(self.to_xyz(observer, illuminant), illuminant.white(observer))
+    }
+
+    //CIEDE2000 between two reflectances under one illuminant
+    pub fn delta_e_under(&self, other: &Spectrum, observer: Observer, illuminant: Illuminant) -> f32 {
+        delta_e_2000(self.to_lab(observer, illuminant), other.to_lab(observer, illuminant))
+    }
+
+    //CIE special metamerism index for a change of illuminant. The other sample is first corrected,
+    //channel by channel in XYZ, so it matches exactly under the reference illuminant, which leaves
+    //only the mismatch caused by the change of light. A channel the other sample shows no response in
+    //has nothing to scale and is left uncorrected.
+    pub fn metamerism_index(&self, other: &Spectrum, observer: Observer, reference: Illuminant, test: Illuminant) -> f32 {
+        let (this_reference, other_reference) = (self.to_xyz(observer, reference), other.to_xyz(observer, reference));
+        let other_test = other.to_xyz(observer, test);
+        let corrected = [0, 1, 2].map(|i| {
+            if other_reference[i] == 0. {
+                other_test[i]
+            } else {
+                other_test[i] * this_reference[i] / other_reference[i]
+            }
+        });
+        let white = test.white(observer);
+        delta_e_2000(self.to_lab(observer, test), xyz_to_lab(corrected, white))
+    }
+
+    //Color difference under each illuminant, in order
+    pub fn delta_e_across(&self, other: &Spectrum, observer: Observer, illuminants: &[Illuminant]) -> Vec<f32> {
+        illuminants.iter().map(|illuminant| self.delta_e_under(other, observer, *illuminant)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observer::{WAVELENGTH_START, WAVELENGTH_STEP};
+    use crate::transformations::{invert_matrix, matrix_transform};
+    use crate::upsampling::Upsampling;
+    use crate::Color;
+
+    #[test]
+    fn test_metameric_pair() {
+        let observer = Observer::Cie1931TwoDegree;
+        let smooth = Color::rgb(0.6, 0.5, 0.4).to_spectrum(Upsampling::JakobHanika);
+        //Add a wiggle the observer cannot see under D65 by cancelling its XYZ with a smooth basis
+        let wiggle = Spectrum::from_samples(WAVELENGTH_START, WAVELENGTH_STEP, (0..41).map(|i| 0.08 * (i as f32 * 0.9).sin()).collect());
+        let wiggle_xyz = wiggle.to_xyz(observer, Illuminant::D65);
+        let basis = [Color::rgb(0.9, 0.1, 0.1), Color::rgb(0.1, 0.9, 0.1), Color::rgb(0.1, 0.1, 0.9)]
+            .map(|c| c.to_spectrum(Upsampling::JakobHanika));
+        let columns = basis.clone().map(|spectrum| spectrum.to_xyz(observer, Illuminant::D65));
+        let matrix = [0, 1, 2].map(|row| [columns[0][row], columns[1][row], columns[2][row]]);
+        let weights = matrix_transform(&invert_matrix(&matrix), wiggle_xyz);
+        let values = (0..41)
+            .map(|i| smooth.values()[i] + wiggle.values()[i] - (0..3).map(|j| weights[j] * basis[j].values()[i]).sum::<f32>())
+            .collect();
+        let metamer = Spectrum::from_samples(WAVELENGTH_START, WAVELENGTH_STEP, values);
+
+        assert!(smooth.delta_e_under(&metamer, observer, Illuminant::D65) < 0.05);
+        assert!(smooth.metamerism_index(&metamer, observer, Illuminant::D65, Illuminant::D65) < 0.05);
+        let differences = smooth.delta_e_across(&metamer, observer, &[Illuminant::D65, Illuminant::A]);
+        assert!(differences[1] > 0.5, "{:?}", differences);
+        assert!(smooth.metamerism_index(&metamer, observer, Illuminant::D65, Illuminant::A) > 0.5);
+
+        //Store lighting: the narrow band tri-phosphor lamp pulls the pair apart too
+        assert!(smooth.metamerism_index(&metamer, observer, Illuminant::D65, Illuminant::F11) > 0.5);
+        let black = Spectrum::constant(0.);
+        let index = smooth.metamerism_index(&black, observer, Illuminant::D65, Illuminant::F11);
+        assert!(index.is_finite() && index > 10., "{}", index);
+        assert_eq!(black.metamerism_index(&black, observer, Illuminant::D65, Illuminant::F2), 0.);
+    }
+
+    #[test]
+    fn test_relight() {
+        let grey = Spectrum::constant(0.5);
+        //An eye adapted to daylight sees a grey card under tungsten as orange
+        let warm = grey.relight(Illuminant::D65, Illuminant::A).to_array();
+        assert!(warm[0] > warm[1] && warm[1] > warm[2]);
+        //Once adapted it is neutral again
+        let adapted = grey.relight(Illuminant::A, Illuminant::A).to_array();
+        assert!((adapted[0] - adapted[2]).abs() < 2e-3, "{:?}", adapted);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::image::{BitDepth, Image, MAX_PIXELS};
+use crate::lut3d::invalid_data;
+use std::io::{self, Read, Write};
+
+//Reads binary and plain PGM and PPM (P2, P3, P5, P6) and PAM (P7) with 1 to 4 channels.
+//Grey is spread over RGB and images without alpha are opaque.
+pub fn read_netpbm(mut reader: impl Read) -> io::Result<Image> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let magic = data.get(..2).ok_or_else(|| invalid_data("empty file"))?;
+    let mut header = Header { data: &data, position: 2 };
+    let (width, height, channels, max, plain) = match magic {
+        b"P2" | b"P3" | b"P5" | b"P6" => {
+            let (width, height, max) = (header.number()?, header.number()?, header.number()?);
+            //A single whitespace byte separates the header from binary data
+            header.position += 1;
+            let channels = if magic[1] == b'2' || magic[1] == b'5' { 1 } else { 3 };
+            (width, height, channels, max, magic[1] == b'2' || magic[1] == b'3')
+        }
+        b"P7" => {
+            let (mut width, mut height, mut channels, mut max) = (0, 0, 0, 0);
+            loop {
+                match header.word()? {
+                    "WIDTH" => width = header.number()?,
+                    "HEIGHT" => height = header.number()?,
+                    "DEPTH" => channels = header.number()?,
+                    "MAXVAL" => max = header.number()?,
+                    "TUPLTYPE" => {
+                        header.word()?;
+                    }
+                    "ENDHDR" => break,
+                    other => return Err(invalid_data(format!("unknown PAM header field {:?}", other))),
+                }
+            }
+            header.position += 1;
+            (width, height, channels, max, false)
+        }
+        _ => return Err(invalid_data("not a Netpbm file")),
+    };
+    if !(1..=4).contains(&channels) || max == 0 || max > 65535 {
+        return Err(invalid_data("unsupported depth or maximum value"));
+    }
+    //Every sample takes at least a byte in either encoding, so the count is checked against the data left
+    let count = width
+        .checked_mul(height)
+        .filter(|pixels| *pixels <= MAX_PIXELS)
+        .map(|pixels| pixels * channels)
+        .filter(|count| *count <= data.len().saturating_sub(header.position))
+        .ok_or_else(|| invalid_data("image size does not match the file"))?;
+    let samples: Vec<f32> = if plain {
+        (0..count).map(|_| header.number().map(|v| v as f32)).collect::<io::Result<_>>()?
+    } else {
+        let bytes = if max > 255 { 2 } else { 1 };
+        let body = data.get(header.position..header.position + count * bytes).ok_or_else(|| invalid_data("file ends early"))?;
+        if bytes == 2 {
+            body.chunks_exact(2).map(|b| u16::from_be_bytes([b[0], b[1]]) as f32).collect()
+        } else {
+            body.iter().map(|b| *b as f32).collect()
+        }
+    };
+    let max = max as f32;
+    let pixels = samples
+        .chunks_exact(channels)
+        .flat_map(|s| match s {
+            [grey] => [*grey, *grey, *grey, max],
+            [grey, alpha] => [*grey, *grey, *grey, *alpha],
+            [r, g, b] => [*r, *g, *b, max],
+            _ => [s[0], s[1], s[2], s[3]],
+        })
+        .map(|v| v / max)
+        .collect();
+    Ok(Image::new(width, height, pixels))
+}
+
+struct Header<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Header<'_> {
+    //Next whitespace separated token, skipping # comments
+    fn word(&mut self) -> io::Result<&str> {
+        loop {
+            match self.data.get(self.position) {
+                Some(b'#') => {
+                    while self.data.get(self.position).is_some_and(|b| *b != b'\n') {
+                        self.position += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => self.position += 1,
+                Some(_) => break,
+                None => return Err(invalid_data("header ends early")),
+            }
+        }
+        let start = self.position;
+        while self.data.get(self.position).is_some_and(|b| !b.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.position]).map_err(|_| invalid_data("header is not text"))
+    }
+
+    fn number(&mut self) -> io::Result<usize> {
+        let word = self.word()?;
+        word.parse().map_err(|_| invalid_data(format!("expected a number, found {:?}", word)))
+    }
+}
+
+fn write_samples(writer: &mut impl Write, samples: impl Iterator<Item = f32>, depth: BitDepth) -> io::Result<()> {
+    let mut body = Vec::new();
+    for sample in samples {
+        let sample = sample.clamp(0., 1.);
+        match depth {
+            BitDepth::Eight => body.push((sample * 255.).round() as u8),
+            BitDepth::Sixteen => body.extend(((sample * 65535.).round() as u16).to_be_bytes()),
+        }
+    }
+    writer.write_all(&body)
+}
+
+fn max_value(depth: BitDepth) -> u32 {
+    match depth {
+        BitDepth::Eight => 255,
+        BitDepth::Sixteen => 65535,
+    }
+}
+
+impl Image {
+    //Binary PPM, alpha is dropped
+    pub fn write_ppm(&self, mut writer: impl Write, depth: BitDepth) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n{}\n", self.width, self.height, max_value(depth))?;
+        let samples = self.pixels.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]]);
+        write_samples(&mut writer, samples, depth)
+    }
+
+    //Binary PGM of the luma the YUV model uses, alpha is dropped
+    pub fn write_pgm(&self, mut writer: impl Write, depth: BitDepth) -> io::Result<()> {
+        write!(writer, "P5\n{} {}\n{}\n", self.width, self.height, max_value(depth))?;
+        let samples = self.pixels.chunks_exact(4).map(|p| 0.299 * p[0] + 0.587 * p[1] + 0.114 * p[2]);
+        write_samples(&mut writer, samples, depth)
+    }
+
+    //PAM with RGB_ALPHA tuples
+    pub fn write_pam(&self, mut writer: impl Write, depth: BitDepth) -> io::Result<()> {
+        write!(
+            writer,
+            "P7\nWIDTH {}\nHEIGHT {}\nDEPTH 4\nMAXVAL {}\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+            self.width,
+            self.height,
+            max_value(depth)
+        )?;
+        write_samples(&mut writer, self.pixels.iter().copied(), depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trips() {
+        let image = Image::new(2, 1, vec![1., 0.5, 0., 0.25, 0.2, 0.4, 0.6, 1.]);
+        let mut pam = Vec::new();
+        image.write_pam(&mut pam, BitDepth::Sixteen).unwrap();
+        let read = read_netpbm(&pam[..]).unwrap();
+        assert_eq!(read.to_rgba16(), image.to_rgba16());
+
+        let mut ppm = Vec::new();
+        image.write_ppm(&mut ppm, BitDepth::Eight).unwrap();
+        assert_eq!(&ppm[..11], b"P6\n2 1\n255\n");
+        assert_eq!(read_netpbm(&ppm[..]).unwrap().to_rgba8(), vec![255, 128, 0, 255, 51, 102, 153, 255]);
+
+        let mut pgm = Vec::new();
+        image.write_pgm(&mut pgm, BitDepth::Eight).unwrap();
+        assert_eq!(read_netpbm(&pgm[..]).unwrap().to_rgba8()[..4], [151, 151, 151, 255]);
+    }
+
+    #[test]
+    fn test_plain_with_comments() {
+        let plain = b"P3\n# made by hand\n2 1 15\n15 0 0  0 15 0\n";
+        let image = read_netpbm(&plain[..]).unwrap();
+        assert_eq!(image.to_rgba8(), vec![255, 0, 0, 255, 0, 255, 0, 255]);
+        assert!(read_netpbm(&b"P6\n2 2\n255\n\x00"[..]).is_err());
+    }
+
+    #[test]
+    fn test_malformed_headers() {
+        let headers: [&[u8]; 4] = [
+            b"P6\n4294967295 4294967295\n255\n\x00",
+            b"P5\n18446744073709551615 2\n255\n\x00",
+            b"P3\n100000 100000\n255\n1 2 3\n",
+            b"P7\nWIDTH 65536\nHEIGHT 65536\nDEPTH 4\nMAXVAL 255\nENDHDR\n\x00",
+        ];
+        for header in headers {
+            assert_eq!(read_netpbm(header).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
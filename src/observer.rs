@@ -0,0 +1,113 @@
+//CIE colour matching functions, sampled every 10nm from 380nm to 780nm
+pub const WAVELENGTH_START: f32 = 380.;
+pub const WAVELENGTH_STEP: f32 = 10.;
+pub const SAMPLE_COUNT: usize = 41;
+
+//CIE 1931 2° standard observer
+pub const CIE_1931_2_DEGREE: [[f32; 3]; SAMPLE_COUNT] = [
+    [0.001368, 0.000039, 0.00645],
+    [0.004243, 0.00012, 0.02005],
+    [0.01431, 0.000396, 0.06785],
+    [0.04351, 0.00121, 0.2074],
+    [0.13438, 0.004, 0.6456],
+    [0.2839, 0.0116, 1.3856],
+    [0.34828, 0.023, 1.74706],
+    [0.3362, 0.038, 1.77211],
+    [0.2908, 0.06, 1.6692],
+    [0.19536, 0.09098, 1.28764],
+    [0.09564, 0.13902, 0.81295],
+    [0.03201, 0.20802, 0.46518],
+    [0.0049, 0.323, 0.272],
+    [0.0093, 0.503, 0.1582],
+    [0.06327, 0.71, 0.07825],
+    [0.1655, 0.862, 0.04216],
+    [0.2904, 0.954, 0.0203],
+    [0.43345, 0.99495, 0.00875],
+    [0.5945, 0.995, 0.0039],
+    [0.7621, 0.952, 0.0021],
+    [0.9163, 0.87, 0.00165],
+    [1.0263, 0.757, 0.0011],
+    [1.0622, 0.631, 0.0008],
+    [1.0026, 0.503, 0.00034],
+    [0.85445, 0.381, 0.00019],
+    [0.6424, 0.265, 0.00005],
+    [0.4479, 0.175, 0.00002],
+    [0.2835, 0.107, 0.],
+    [0.1649, 0.061, 0.],
+    [0.0874, 0.032, 0.],
+    [0.04677, 0.017, 0.],
+    [0.0227, 0.00821, 0.],
+    [0.011359, 0.004102, 0.],
+    [0.00579, 0.002091, 0.],
+    [0.002899, 0.001047, 0.],
+    [0.00144, 0.00052, 0.],
+    [0.00069, 0.000249, 0.],
+    [0.000332, 0.00012, 0.],
+    [0.000166, 0.00006, 0.],
+    [0.000083, 0.00003, 0.],
+    [0.000042, 0.000015, 0.],
+];
+
+//CIE 1964 10° supplementary standard observer
+pub const CIE_1964_10_DEGREE: [[f32; 3]; SAMPLE_COUNT] = [
+    [0.00016, 0.000017, 0.000705],
+    [0.002362, 0.000253, 0.010482],
+    [0.01911, 0.002004, 0.086011],
+    [0.084736, 0.008756, 0.389366],
+    [0.204492, 0.021391, 0.972542],
+    [0.314679, 0.038676, 1.55348],
+    [0.383734, 0.062077, 1.96728],
+    [0.370702, 0.089456, 1.9948],
+    [0.302273, 0.128201, 1.74537],
+    [0.195618, 0.18519, 1.31756],
+    [0.080507, 0.253589, 0.772125],
+    [0.016172, 0.339133, 0.415254],
+    [0.003816, 0.460777, 0.218502],
+    [0.037465, 0.606741, 0.112044],
+    [0.117749, 0.761757, 0.060709],
+    [0.236491, 0.875211, 0.030451],
+    [0.376772, 0.961988, 0.013676],
+    [0.529826, 0.991761, 0.003988],
+    [0.705224, 0.99734, 0.],
+    [0.878655, 0.955552, 0.],
+    [1.01416, 0.868934, 0.],
+    [1.11852, 0.777405, 0.],
+    [1.12399, 0.658341, 0.],
+    [1.03048, 0.527963, 0.],
+    [0.856297, 0.398057, 0.],
+    [0.647467, 0.283493, 0.],
+    [0.431567, 0.179828, 0.],
+    [0.268329, 0.107633, 0.],
+    [0.152568, 0.060281, 0.],
+    [0.081261, 0.0318, 0.],
+    [0.040851, 0.015905, 0.],
+    [0.019941, 0.007749, 0.],
+    [0.009577, 0.003718, 0.],
+    [0.004553, 0.001768, 0.],
+    [0.002175, 0.000846, 0.],
+    [0.001045, 0.000407, 0.],
+    [0.000508, 0.000199, 0.],
+    [0.000251, 0.000098, 0.],
+    [0.000126, 0.00005, 0.],
+    [0.000065, 0.000025, 0.],
+    [0.000033, 0.000013, 0.],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Observer {
+    Cie1931TwoDegree,
+    Cie1964TenDegree,
+}
+
+impl Observer {
+    pub fn table(&self) -> &'static [[f32; 3]; SAMPLE_COUNT] {
+        match self {
+            Observer::Cie1931TwoDegree => &CIE_1931_2_DEGREE,
+            Observer::Cie1964TenDegree => &CIE_1964_10_DEGREE,
+        }
+    }
+}
+
+pub fn wavelength(index: usize) -> f32 {
+    WAVELENGTH_START + WAVELENGTH_STEP * index as f32
+}
@@ -0,0 +1,100 @@
+use crate::ColorModel;
+use crate::batch::{convert_integer_slice, convert_slice};
+use crate::transformations::DefinedColor;
+use num_traits::{AsPrimitive, PrimInt, Unsigned};
+use std::thread;
+
+//Every worker gets a contiguous run of whole pixels and does exactly what the serial
+//path does to it, so the output does not depend on the thread count.
+
+//Fewest pixels worth handing to a thread of their own
+const MIN_CHUNK: usize = 1024;
+
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+//The requested thread count, never more than the machine has or than there are chunks to share
+fn worker_count(pixels: usize, threads: usize) -> usize {
+    threads.min(available_threads()).min(pixels / MIN_CHUNK).max(1)
+}
+
+fn split_work<T: Sync, U: Send>(input: &[T], output: &mut [U], threads: usize, work: impl Fn(&[T], &mut [U]) + Sync) {
+    assert_eq!(input.len(), output.len(), "input and output must be the same length");
+    assert_eq!(input.len() % 4, 0, "pixels have four components");
+    let pixels = input.len() / 4;
+    let per_thread = pixels.div_ceil(worker_count(pixels, threads)).max(1) * 4;
+    if per_thread >= input.len() {
+        return work(input, output);
+    }
+    thread::scope(|scope| {
+        for (source, destination) in input.chunks(per_thread).zip(output.chunks_mut(per_thread)) {
+            let work = &work;
+            scope.spawn(move || work(source, destination));
+        }
+    });
+}
+
+pub fn convert_slice_parallel(input: &[f32], from: ColorModel, to: ColorModel, output: &mut [f32], threads: usize) {
+    split_work(input, output, threads, |source, destination| convert_slice(source, from, to, destination));
+}
+
+pub fn convert_integer_slice_parallel<T>(input: &[T], from: ColorModel, to: ColorModel, output: &mut [T], threads: usize)
+where
+    T: Unsigned + PrimInt + AsPrimitive<f32> + Send + Sync + 'static,
+    f32: AsPrimitive<T>,
+{
+    split_work(input, output, threads, |source, destination| convert_integer_slice(source, from, to, destination));
+}
+
+//Applies the curves of a DefinedColor to every pixel, the color it was defined with is ignored
+pub fn apply_curves_parallel(input: &[f32], curves: &DefinedColor, output: &mut [f32], threads: usize) {
+    split_work(input, output, threads, |source, destination| {
+        for (pixel, mapped) in source.chunks_exact(4).zip(destination.chunks_exact_mut(4)) {
+            mapped.copy_from_slice(&curves.map_components([pixel[0], pixel[1], pixel[2], pixel[3]]));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Color;
+
+    #[test]
+    fn test_identical_to_serial() {
+        let input: Vec<f32> = (0..4099).flat_map(|i| [(i % 17) as f32 / 16., (i % 29) as f32 / 28., (i % 7) as f32 / 6., 1.]).collect();
+        let mut serial = vec![0.; input.len()];
+        convert_slice(&input, ColorModel::RGBA, ColorModel::SphericalHCLA, &mut serial);
+        for threads in [1, 3, 8] {
+            let mut output = vec![0.; input.len()];
+            convert_slice_parallel(&input, ColorModel::RGBA, ColorModel::SphericalHCLA, &mut output, threads);
+            assert_eq!(output, serial);
+        }
+
+        let bytes: Vec<u8> = (0..4096u32).map(|i| (i * 7 % 256) as u8).collect();
+        let (mut serial, mut output) = (vec![0; bytes.len()], vec![0; bytes.len()]);
+        convert_integer_slice(&bytes, ColorModel::RGBA, ColorModel::CubicHSLA, &mut serial);
+        convert_integer_slice_parallel(&bytes, ColorModel::RGBA, ColorModel::CubicHSLA, &mut output, available_threads());
+        assert_eq!(output, serial);
+    }
+
+    #[test]
+    fn test_thread_count_is_clamped() {
+        let available = available_threads();
+        assert_eq!(worker_count(1 << 30, 5000), available);
+        assert_eq!(worker_count(1 << 30, 0), 1);
+        assert_eq!(worker_count(10, 8), 1);
+        assert_eq!(worker_count(4 * MIN_CHUNK, 5000), available.min(4));
+        assert_eq!(worker_count(4 * MIN_CHUNK - 1, 5000), available.min(3));
+    }
+
+    #[test]
+    fn test_curves() {
+        let input = [0.25, 0.5, 1., 1., 0.04, 0.81, 0., 0.5];
+        let mut output = [0.; 8];
+        let curves = DefinedColor::gamma(Color::rgb(0., 0., 0.), 0.5);
+        apply_curves_parallel(&input, &curves, &mut output, 2);
+        assert_eq!(output, input.map(|c| c.powf(2.)));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::Color;
+use crate::illuminant::Illuminant;
+use crate::observer::{WAVELENGTH_START, WAVELENGTH_STEP};
+use crate::spectrum::Spectrum;
+use crate::upsampling::Upsampling;
+
+//Keeps K/S finite for pigments that reflect nothing at some wavelength
+const MIN_REFLECTANCE: f32 = 1e-4;
+
+//Single constant Kubelka-Munk mixing: every color is upsampled to a reflectance, converted
+//to its absorption over scattering ratio K/S, the ratios are averaged by weight and turned back
+//into a reflectance seen under D65. Alpha is mixed linearly.
+//None when there is nothing to mix: weights must be finite, none below zero, adding up to more than zero.
+pub fn mix_pigments(pigments: &[(Color, f32)]) -> Option<Color> {
+    if pigments.iter().any(|(_, weight)| !weight.is_finite() || *weight < 0.) {
+        return None;
+    }
+    let total: f32 = pigments.iter().map(|(_, weight)| weight).sum();
+    if total <= 0. {
+        return None;
+    }
+    let mut mixed: Vec<f32> = Vec::new();
+    let mut alpha = 0.;
+    for (color, weight) in pigments {
+        let spectrum = color.to_spectrum(Upsampling::JakobHanika);
+        mixed.resize(spectrum.values().len(), 0.);
+        for (sum, reflectance) in mixed.iter_mut().zip(spectrum.values()) {
+            *sum += weight / total * absorption_over_scattering(*reflectance);
+        }
+        alpha += weight / total * color.to_rgb().components[3];
+    }
+    let reflectance = mixed.into_iter().map(reflectance_from_ratio).collect();
+    let color = Spectrum::from_samples(WAVELENGTH_START, WAVELENGTH_STEP, reflectance)
+        .to_color(Illuminant::D65)
+        .set_alpha(alpha);
+    Some(color)
+}
+
+fn absorption_over_scattering(reflectance: f32) -> f32 {
+    let r = reflectance.clamp(MIN_REFLECTANCE, 1.);
+    (1. - r) * (1. - r) / (2. * r)
+}
+
+fn reflectance_from_ratio(ratio: f32) -> f32 {
+    1. + ratio - (ratio * ratio + 2. * ratio).sqrt()
+}
+
+impl Color {
+    //Paint-like mix of two colors, amount 0 is self and 1 is other, clamped between the two.
+    //A NaN amount returns self.
+    pub fn mix_pigment(&self, other: &Color, amount: f32) -> Color {
+        let amount = amount.clamp(0., 1.);
+        mix_pigments(&[(*self, 1. - amount), (*other, amount)])
+            .map_or(*self, |mixed| mixed.convert_color(self.color_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ColorModel;
+
+    #[test]
+    fn test_yellow_and_blue_make_green() {
+        let green = mix_pigments(&[(Color::rgb(1., 0.9, 0.), 1.), (Color::rgb(0., 0.2, 0.8), 1.)]).unwrap().to_array();
+        assert!(green[1] > green[0] && green[1] > green[2], "{:?}", green);
+        assert_eq!(green[3], 1.);
+
+        //A lone pigment comes back as itself
+        let color = Color::rgb(0.7, 0.4, 0.3);
+        let alone = mix_pigments(&[(color, 2.)]).unwrap().to_array();
+        for (a, b) in alone.iter().zip(color.to_array().iter()) {
+            assert!((a - b).abs() < 5e-3);
+        }
+        assert_eq!(Color::cubic_hsv(0.1, 0.5, 0.5).mix_pigment(&color, 0.5).color_type, ColorModel::CubicHSVA);
+
+        //Nothing to mix
+        assert_eq!(mix_pigments(&[]), None);
+        assert_eq!(mix_pigments(&[(color, 0.), (Color::rgb(0., 0., 1.), 0.)]), None);
+        assert_eq!(mix_pigments(&[(Color::rgb(1., 0., 0.), -1.), (Color::rgb(0., 0., 1.), 2.)]), None);
+        assert_eq!(mix_pigments(&[(color, f32::INFINITY)]), None);
+
+        //Amounts past either end stay on the nearer color
+        let blue = Color::rgb(0., 0., 1.);
+        assert_eq!(color.mix_pigment(&blue, 1.5), color.mix_pigment(&blue, 1.));
+        assert_eq!(color.mix_pigment(&blue, -0.5), color.mix_pigment(&blue, 0.));
+        assert!(color.mix_pigment(&blue, 1.5).to_array().iter().all(|c| c.is_finite()));
+    }
+}
@@ -0,0 +1,183 @@
+use crate::image::{BitDepth, Image};
+use crate::rgb_space::{RgbSpace, TransferFunction};
+use std::io::{self, Write};
+
+const SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+//Largest payload of a stored deflate block
+const STORED_BLOCK: usize = 65535;
+//The zlib stream is split over IDAT chunks of at most this many bytes, chunk lengths can't pass 2³¹ - 1
+const IDAT_SIZE: usize = 1 << 20;
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { 0xedb88320 ^ (crc >> 1) } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    //5552 is the most bytes before the sums can overflow
+    for chunk in bytes.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        (a, b) = (a % 65521, b % 65521);
+    }
+    b << 16 | a
+}
+
+//A zlib stream of stored blocks, no compression
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut stream = Vec::with_capacity(data.len() + data.len() / STORED_BLOCK * 5 + 11);
+    stream.extend([0x78, 0x01]);
+    let mut blocks = data.chunks(STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        stream.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let length = block.len() as u16;
+        stream.push(if blocks.peek().is_none() { 1 } else { 0 });
+        stream.extend(length.to_le_bytes());
+        stream.extend((!length).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend(adler32(data).to_be_bytes());
+    stream
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    debug_assert!(data.len() <= i32::MAX as usize, "PNG chunks hold at most 2³¹ - 1 bytes");
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+//The decoding exponent gAMA stores. Curves with a linear toe use the least squares power fit.
+fn file_gamma(transfer: TransferFunction) -> f32 {
+    match transfer {
+        TransferFunction::Linear => 1.,
+        TransferFunction::Srgb => 2.2,
+        TransferFunction::Gamma(gamma) => gamma,
+        TransferFunction::Rec709 => 1.925,
+        TransferFunction::ProPhoto => 1.8,
+    }
+}
+
+impl Image {
+    //RGB when every pixel is opaque, otherwise RGBA. The pixels are taken to be encoded in the given space:
+    //sRGB gets an sRGB chunk, every space gets gAMA and cHRM so viewers without color management still cope.
+    pub fn write_png(&self, mut writer: impl Write, depth: BitDepth, space: &RgbSpace) -> io::Result<()> {
+        if self.width == 0 || self.height == 0 || self.width > i32::MAX as usize || self.height > i32::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "PNG images need a size between 1 and 2³¹ - 1"));
+        }
+        let opaque = self.is_opaque();
+        let channels = if opaque { 3 } else { 4 };
+        let (bits, color_type) = (if depth == BitDepth::Eight { 8u8 } else { 16 }, if opaque { 2u8 } else { 6 });
+
+        let mut png = Vec::new();
+        png.extend(SIGNATURE);
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        header.extend([bits, color_type, 0, 0, 0]);
+        chunk(&mut png, b"IHDR", &header);
+
+        if *space == RgbSpace::SRGB {
+            //Perceptual rendering intent
+            chunk(&mut png, b"sRGB", &[0]);
+        }
+        let gamma = (100000. / file_gamma(space.transfer)).round() as u32;
+        chunk(&mut png, b"gAMA", &gamma.to_be_bytes());
+        let chromaticities: Vec<u8> = [space.white, space.red, space.green, space.blue]
+            .iter()
+            .flatten()
+            .flat_map(|c| ((c * 100000.).round() as u32).to_be_bytes())
+            .collect();
+        chunk(&mut png, b"cHRM", &chromaticities);
+
+        //Every scanline starts with filter type 0, none
+        let samples: Vec<u8> = match depth {
+            BitDepth::Eight => self.to_rgba8(),
+            BitDepth::Sixteen => self.to_rgba16().into_iter().flat_map(u16::to_be_bytes).collect(),
+        };
+        let sample_bytes = bits as usize / 8;
+        let mut raw = Vec::with_capacity(self.height * (1 + self.width * channels * sample_bytes));
+        for row in samples.chunks_exact(self.width * 4 * sample_bytes) {
+            raw.push(0);
+            for pixel in row.chunks_exact(4 * sample_bytes) {
+                raw.extend_from_slice(&pixel[..channels * sample_bytes]);
+            }
+        }
+        for data in zlib_stored(&raw).chunks(IDAT_SIZE) {
+            chunk(&mut png, b"IDAT", data);
+        }
+        chunk(&mut png, b"IEND", &[]);
+        writer.write_all(&png)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::QUATERNARY_COLORS;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae426082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+        let data = vec![7u8; STORED_BLOCK + 10];
+        let stream = zlib_stored(&data);
+        assert_eq!(stream.len(), 2 + 2 * 5 + data.len() + 4);
+        assert_eq!(&stream[2..7], &[0, 0xff, 0xff, 0, 0]);
+    }
+
+    #[test]
+    fn test_swatch_sheet() {
+        let grid: Vec<Vec<_>> = QUATERNARY_COLORS.iter().map(|row| row.to_vec()).collect();
+        let sheet = Image::swatch_sheet(&grid, 8);
+        let mut png = Vec::new();
+        sheet.write_png(&mut png, BitDepth::Eight, &RgbSpace::SRGB).unwrap();
+        assert_eq!(&png[..8], &SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[33 + 4..33 + 8], b"sRGB");
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+        //The gAMA for sRGB is the conventional 45455
+        let gamma = png.windows(4).position(|w| w == b"gAMA").unwrap() + 4;
+        assert_eq!(u32::from_be_bytes([png[gamma], png[gamma + 1], png[gamma + 2], png[gamma + 3]]), 45455);
+    }
+
+    #[test]
+    fn test_idat_is_split() {
+        let (width, height) = (600, 600);
+        let pixels = (0..width * height * 4).map(|i| (i % 251) as f32 / 250.).collect();
+        let image = Image::new(width, height, pixels);
+        let mut png = Vec::new();
+        image.write_png(&mut png, BitDepth::Eight, &RgbSpace::SRGB).unwrap();
+
+        //Walks the chunks, gathering the IDAT payloads back into one zlib stream
+        let (mut position, mut stream, mut idats) = (8, Vec::new(), 0);
+        while position < png.len() {
+            let length = u32::from_be_bytes([png[position], png[position + 1], png[position + 2], png[position + 3]]) as usize;
+            let end = position + 8 + length;
+            assert_eq!(crc32(&png[position + 4..end]).to_be_bytes(), png[end..end + 4]);
+            if &png[position + 4..position + 8] == b"IDAT" {
+                assert!(length <= IDAT_SIZE);
+                stream.extend_from_slice(&png[position + 8..end]);
+                idats += 1;
+            }
+            position = end + 4;
+        }
+        assert_eq!(idats, 2);
+        let raw_length = height * (1 + width * 4);
+        assert_eq!(stream.len(), 2 + raw_length.div_ceil(STORED_BLOCK) * 5 + raw_length + 4);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::image::{Image, MAX_PIXELS};
+use crate::lut3d::invalid_data;
+use crate::rgb_space::TransferFunction;
+use std::io::{self, Read, Write};
+
+//The Quite OK Image format, https://qoiformat.org/qoi-specification.pdf
+const MAGIC: &[u8; 4] = b"qoif";
+const END: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xc0;
+const OP_RGB: u8 = 0xfe;
+const OP_RGBA: u8 = 0xff;
+const MASK: u8 = 0xc0;
+
+fn hash(pixel: [u8; 4]) -> usize {
+    let [r, g, b, a] = pixel.map(|c| c as usize);
+    (r * 3 + g * 5 + b * 7 + a * 11) % 64
+}
+
+//The header's colorspace byte is the transfer of the RGB, sRGB or linear; alpha is always linear
+pub fn read_qoi(mut reader: impl Read) -> io::Result<(Image, TransferFunction)> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    if data.len() < 14 + END.len() || &data[..4] != MAGIC {
+        return Err(invalid_data("not a QOI file"));
+    }
+    let width = u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize;
+    let height = u32::from_be_bytes([data[8], data[9], data[10], data[11]]) as usize;
+    let transfer = match data[13] {
+        0 => TransferFunction::Srgb,
+        1 => TransferFunction::Linear,
+        _ => return Err(invalid_data("unknown QOI colorspace")),
+    };
+    if !(data[12] == 3 || data[12] == 4) || width.saturating_mul(height) > MAX_PIXELS {
+        return Err(invalid_data("bad QOI header"));
+    }
+
+    //A run byte covers at most 62 pixels, so a short file can't hold a large image
+    let count = width * height;
+    if count > (data.len() - 14 - END.len()) * 62 {
+        return Err(invalid_data("QOI data ends early"));
+    }
+    let mut pixels: Vec<u8> = Vec::with_capacity(count * 4);
+    let mut index = [[0u8; 4]; 64];
+    let mut pixel = [0, 0, 0, 255];
+    let mut position = 14;
+    let chunks = &data[..data.len() - END.len()];
+    let mut next = || {
+        let byte = chunks.get(position).copied().ok_or_else(|| invalid_data("QOI data ends early"));
+        position += 1;
+        byte
+    };
+    while pixels.len() < count * 4 {
+        let op = next()?;
+        let mut run = 1;
+        if op == OP_RGB {
+            pixel = [next()?, next()?, next()?, pixel[3]];
+        } else if op == OP_RGBA {
+            pixel = [next()?, next()?, next()?, next()?];
+        } else {
+            match op & MASK {
+                OP_INDEX => pixel = index[op as usize],
+                OP_DIFF => {
+                    pixel[0] = pixel[0].wrapping_add((op >> 4 & 3).wrapping_sub(2));
+                    pixel[1] = pixel[1].wrapping_add((op >> 2 & 3).wrapping_sub(2));
+                    pixel[2] = pixel[2].wrapping_add((op & 3).wrapping_sub(2));
+                }
+                OP_LUMA => {
+                    let second = next()?;
+                    let green = (op & 0x3f).wrapping_sub(32);
+                    pixel[0] = pixel[0].wrapping_add(green.wrapping_sub(8).wrapping_add(second >> 4));
+                    pixel[1] = pixel[1].wrapping_add(green);
+                    pixel[2] = pixel[2].wrapping_add(green.wrapping_sub(8).wrapping_add(second & 0x0f));
+                }
+                _ => run = (op & 0x3f) as usize + 1,
+            }
+        }
+        index[hash(pixel)] = pixel;
+        for _ in 0..run.min(count - pixels.len() / 4) {
+            pixels.extend_from_slice(&pixel);
+        }
+    }
+    Ok((Image::from_integers(width, height, &pixels), transfer))
+}
+
+impl Image {
+    //Written with three channels when every pixel is opaque. QOI only tags sRGB and linear RGB.
+    pub fn write_qoi(&self, mut writer: impl Write, transfer: TransferFunction) -> io::Result<()> {
+        let colorspace = match transfer {
+            TransferFunction::Srgb => 0,
+            TransferFunction::Linear => 1,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "QOI only stores sRGB or linear RGB")),
+        };
+        if self.width > u32::MAX as usize || self.height > u32::MAX as usize || self.width * self.height > MAX_PIXELS {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "image is too large for QOI"));
+        }
+        let mut data = Vec::with_capacity(14 + self.pixels.len() + END.len());
+        data.extend_from_slice(MAGIC);
+        data.extend((self.width as u32).to_be_bytes());
+        data.extend((self.height as u32).to_be_bytes());
+        data.extend([if self.is_opaque() { 3 } else { 4 }, colorspace]);
+
+        let mut index = [[0u8; 4]; 64];
+        let mut previous = [0, 0, 0, 255];
+        let mut run = 0u8;
+        let rgba = self.to_rgba8();
+        let pixels = rgba.chunks_exact(4).map(|p| [p[0], p[1], p[2], p[3]]);
+        let last = self.width * self.height;
+        for (i, pixel) in pixels.enumerate() {
+            if pixel == previous {
+                run += 1;
+                if run == 62 || i + 1 == last {
+                    data.push(OP_RUN | (run - 1));
+                    run = 0;
+                }
+                continue;
+            }
+            if run > 0 {
+                data.push(OP_RUN | (run - 1));
+                run = 0;
+            }
+            let slot = hash(pixel);
+            if index[slot] == pixel {
+                data.push(OP_INDEX | slot as u8);
+            } else {
+                index[slot] = pixel;
+                if pixel[3] == previous[3] {
+                    let [dr, dg, db] = [0, 1, 2].map(|c| pixel[c].wrapping_sub(previous[c]) as i8);
+                    let (dr_dg, db_dg) = (dr.wrapping_sub(dg), db.wrapping_sub(dg));
+                    if (-2..2).contains(&dr) && (-2..2).contains(&dg) && (-2..2).contains(&db) {
+                        data.push(OP_DIFF | ((dr + 2) as u8) << 4 | ((dg + 2) as u8) << 2 | (db + 2) as u8);
+                    } else if (-32..32).contains(&dg) && (-8..8).contains(&dr_dg) && (-8..8).contains(&db_dg) {
+                        data.push(OP_LUMA | (dg + 32) as u8);
+                        data.push(((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8);
+                    } else {
+                        data.extend([OP_RGB, pixel[0], pixel[1], pixel[2]]);
+                    }
+                } else {
+                    data.extend([OP_RGBA, pixel[0], pixel[1], pixel[2], pixel[3]]);
+                }
+            }
+            previous = pixel;
+        }
+        data.extend(END);
+        writer.write_all(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tones::tone_table;
+
+    #[test]
+    fn test_round_trip() {
+        let image = Image::from_grid(&tone_table(24, 4));
+        let mut data = Vec::new();
+        image.write_qoi(&mut data, TransferFunction::Srgb).unwrap();
+        //The table starts with a transparent swatch
+        assert_eq!(data[12..14], [4, 0]);
+        let (read, transfer) = read_qoi(&data[..]).unwrap();
+        assert_eq!(transfer, TransferFunction::Srgb);
+        assert_eq!(read.to_rgba8(), image.to_rgba8());
+
+        //Runs, small differences and alpha changes
+        let pixels: Vec<u8> = (0..300u32).flat_map(|i| [(i / 70) as u8 * 3, 100 + (i / 50) as u8, 7, if i % 97 == 0 { 128 } else { 255 }]).collect();
+        let image = Image::from_integers(20, 15, &pixels);
+        let mut data = Vec::new();
+        image.write_qoi(&mut data, TransferFunction::Linear).unwrap();
+        assert!(data.len() < pixels.len() / 4);
+        let (read, transfer) = read_qoi(&data[..]).unwrap();
+        assert_eq!((read.to_rgba8(), transfer), (pixels, TransferFunction::Linear));
+        assert!(image.write_qoi(Vec::new(), TransferFunction::Gamma(2.2)).is_err());
+    }
+
+    #[test]
+    fn test_huge_header_on_tiny_file() {
+        let mut data = MAGIC.to_vec();
+        data.extend(20000u32.to_be_bytes());
+        data.extend(20000u32.to_be_bytes());
+        data.extend([4, 0]);
+        data.extend([OP_RUN | 61; 8]);
+        data.extend(END);
+        assert_eq!(read_qoi(&data[..]).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
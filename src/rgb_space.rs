@@ -0,0 +1,196 @@
+use crate::{Color, ColorModel};
+use crate::adaptation::{adapt_xyz, AdaptationMethod};
+use crate::cie::xy_to_xyz;
+use crate::transformations::{invert_matrix, linear_to_srgb, matrix_transform, srgb_to_linear};
+
+type Matrix = [[f32; 3]; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferFunction {
+    Linear,
+    Srgb,
+    Gamma(f32),
+    //ITU-R BT.709 and BT.2020 camera curve
+    Rec709,
+    //ROMM RGB
+    ProPhoto,
+}
+
+impl TransferFunction {
+    //Encoded to linear light, negative values are mirrored
+    pub fn decode(&self, value: f32) -> f32 {
+        let (sign, value) = (value.signum(), value.abs());
+        sign * match self {
+            TransferFunction::Linear => value,
+            TransferFunction::Srgb => srgb_to_linear(value),
+            TransferFunction::Gamma(gamma) => value.powf(*gamma),
+            TransferFunction::Rec709 => {
+                if value < 0.081 { value / 4.5 } else { ((value + 0.099) / 1.099).powf(1. / 0.45) }
+            }
+            TransferFunction::ProPhoto => {
+                if value < 16. / 512. { value / 16. } else { value.powf(1.8) }
+            }
+        }
+    }
+
+    pub fn encode(&self, value: f32) -> f32 {
+        let (sign, value) = (value.signum(), value.abs());
+        sign * match self {
+            TransferFunction::Linear => value,
+            TransferFunction::Srgb => linear_to_srgb(value),
+            TransferFunction::Gamma(gamma) => value.powf(1. / gamma),
+            TransferFunction::Rec709 => {
+                if value < 0.018 { value * 4.5 } else { 1.099 * value.powf(0.45) - 0.099 }
+            }
+            TransferFunction::ProPhoto => {
+                if value < 1. / 512. { value * 16. } else { value.powf(1. / 1.8) }
+            }
+        }
+    }
+}
+
+//An RGB working space from its primaries and white point as CIE xy chromaticities
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RgbSpace {
+    pub red: [f32; 2],
+    pub green: [f32; 2],
+    pub blue: [f32; 2],
+    pub white: [f32; 2],
+    pub transfer: TransferFunction,
+}
+
+impl RgbSpace {
+    pub const SRGB: RgbSpace = RgbSpace {
+        red: [0.64, 0.33],
+        green: [0.3, 0.6],
+        blue: [0.15, 0.06],
+        white: [0.3127, 0.329],
+        transfer: TransferFunction::Srgb,
+    };
+    pub const DISPLAY_P3: RgbSpace = RgbSpace {
+        red: [0.68, 0.32],
+        green: [0.265, 0.69],
+        blue: [0.15, 0.06],
+        white: [0.3127, 0.329],
+        transfer: TransferFunction::Srgb,
+    };
+    pub const ADOBE_RGB: RgbSpace = RgbSpace {
+        red: [0.64, 0.33],
+        green: [0.21, 0.71],
+        blue: [0.15, 0.06],
+        white: [0.3127, 0.329],
+        transfer: TransferFunction::Gamma(563. / 256.),
+    };
+    pub const REC_709: RgbSpace = RgbSpace {
+        red: [0.64, 0.33],
+        green: [0.3, 0.6],
+        blue: [0.15, 0.06],
+        white: [0.3127, 0.329],
+        transfer: TransferFunction::Rec709,
+    };
+    pub const REC_2020: RgbSpace = RgbSpace {
+        red: [0.708, 0.292],
+        green: [0.17, 0.797],
+        blue: [0.131, 0.046],
+        white: [0.3127, 0.329],
+        transfer: TransferFunction::Rec709,
+    };
+    pub const PROPHOTO: RgbSpace = RgbSpace {
+        red: [0.7347, 0.2653],
+        green: [0.1596, 0.8404],
+        blue: [0.0366, 0.0001],
+        white: [0.3457, 0.3585],
+        transfer: TransferFunction::ProPhoto,
+    };
+    pub const ACESCG: RgbSpace = RgbSpace {
+        red: [0.713, 0.293],
+        green: [0.165, 0.83],
+        blue: [0.128, 0.044],
+        white: [0.32168, 0.33767],
+        transfer: TransferFunction::Linear,
+    };
+
+    //Linear RGB to XYZ, scaled so the white point has Y = 1
+    pub fn to_xyz_matrix(&self) -> Matrix {
+        let columns = [self.red, self.green, self.blue].map(xy_to_xyz);
+        let primaries = [0, 1, 2].map(|row| [columns[0][row], columns[1][row], columns[2][row]]);
+        let scale = matrix_transform(&invert_matrix(&primaries), xy_to_xyz(self.white));
+        primaries.map(|row| [row[0] * scale[0], row[1] * scale[1], row[2] * scale[2]])
+    }
+
+    pub fn from_xyz_matrix(&self) -> Matrix {
+        invert_matrix(&self.to_xyz_matrix())
+    }
+
+    //Encoded RGB in this space to CIE XYZ
+    pub fn to_xyz(&self, rgb: [f32; 3]) -> [f32; 3] {
+        matrix_transform(&self.to_xyz_matrix(), rgb.map(|c| self.transfer.decode(c)))
+    }
+
+    pub fn from_xyz(&self, xyz: [f32; 3]) -> [f32; 3] {
+        matrix_transform(&self.from_xyz_matrix(), xyz).map(|c| self.transfer.encode(c))
+    }
+
+    //Re-expresses encoded RGB from this space in the target space through XYZ,
+    //adapting between the white points with Bradford
+    pub fn convert(&self, rgb: [f32; 3], target: &RgbSpace) -> [f32; 3] {
+        self.convert_with(rgb, target, Some(AdaptationMethod::Bradford))
+    }
+
+    //Without an adaptation method the conversion is absolute and the source white keeps its tint
+    pub fn convert_with(&self, rgb: [f32; 3], target: &RgbSpace, adaptation: Option<AdaptationMethod>) -> [f32; 3] {
+        let xyz = self.to_xyz(rgb);
+        let xyz = match adaptation {
+            Some(method) => adapt_xyz(xyz, self.white, target.white, method),
+            None => xyz,
+        };
+        target.from_xyz(xyz)
+    }
+}
+
+impl Color {
+    //Reads the color's RGB as living in one space and returns the same color, in the same model,
+    //with its RGB expressed in the other space
+    pub fn convert_rgb_space(&self, from: &RgbSpace, to: &RgbSpace) -> Color {
+        let [r, g, b, a] = self.to_rgb().components;
+        let [r, g, b] = from.convert([r, g, b], to);
+        Color::from_array([r, g, b, a], ColorModel::RGBA).convert_color(self.color_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srgb_matrix() {
+        let expected = [
+            [0.4124564, 0.3575761, 0.1804375],
+            [0.2126729, 0.7151522, 0.072175],
+            [0.0193339, 0.119192, 0.9503041],
+        ];
+        let matrix = RgbSpace::SRGB.to_xyz_matrix();
+        for (row, expected) in matrix.iter().zip(expected.iter()) {
+            for (value, expected) in row.iter().zip(expected.iter()) {
+                assert!((value - expected).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_conversions_between_spaces() {
+        let spaces = [RgbSpace::SRGB, RgbSpace::DISPLAY_P3, RgbSpace::ADOBE_RGB, RgbSpace::REC_709, RgbSpace::REC_2020, RgbSpace::PROPHOTO, RgbSpace::ACESCG];
+        let color = Color::cubic_hsv(0.3, 0.6, 0.8);
+        for space in spaces {
+            let there = color.convert_rgb_space(&RgbSpace::SRGB, &space);
+            let back = there.convert_rgb_space(&space, &RgbSpace::SRGB);
+            assert_eq!(back.color_type, ColorModel::CubicHSVA);
+            for (a, b) in back.to_rgb().to_array().iter().zip(color.to_rgb().to_array().iter()) {
+                assert!((a - b).abs() < 1e-3);
+            }
+        }
+        //Display P3 red lies outside sRGB
+        let red = Color::rgb(1., 0., 0.).convert_rgb_space(&RgbSpace::DISPLAY_P3, &RgbSpace::SRGB);
+        assert!(!red.in_gamut());
+    }
+}
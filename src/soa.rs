@@ -0,0 +1,216 @@
+use std::f32::consts::PI;
+
+//Structure of arrays kernels. Each one converts three planes in place, alpha is untouched.
+//Sectors are picked with selects instead of matches so the loops have no data dependent branches;
+//the trig in the spherical model is still a scalar libm call per pixel.
+
+//Separate r, g, b, a planes of an image
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Planes {
+    pub channels: [Vec<f32>; 4],
+}
+
+impl Planes {
+    pub fn from_interleaved(pixels: &[f32]) -> Planes {
+        assert_eq!(pixels.len() % 4, 0, "pixels have four components");
+        let mut channels: [Vec<f32>; 4] = Default::default();
+        for channel in channels.iter_mut() {
+            channel.reserve(pixels.len() / 4);
+        }
+        for pixel in pixels.chunks_exact(4) {
+            for (channel, value) in channels.iter_mut().zip(pixel) {
+                channel.push(*value);
+            }
+        }
+        Planes { channels }
+    }
+
+    pub fn to_interleaved(&self) -> Vec<f32> {
+        let [a, b, c, d] = &self.channels;
+        a.iter().zip(b).zip(c).zip(d).flat_map(|(((a, b), c), d)| [*a, *b, *c, *d]).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.channels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn split(&mut self) -> (&mut [f32], &mut [f32], &mut [f32]) {
+        let [a, b, c, _] = &mut self.channels;
+        (a, b, c)
+    }
+
+    pub fn rgb_to_cubic_hsv(&mut self) {
+        let (r, g, b) = self.split();
+        rgb_to_cubic_hsv(r, g, b)
+    }
+
+    pub fn cubic_hsv_to_rgb(&mut self) {
+        let (h, s, v) = self.split();
+        cubic_hsv_to_rgb(h, s, v)
+    }
+
+    pub fn rgb_to_spherical_hcl(&mut self) {
+        let (r, g, b) = self.split();
+        rgb_to_spherical_hcl(r, g, b)
+    }
+
+    pub fn spherical_hcl_to_rgb(&mut self) {
+        let (h, c, l) = self.split();
+        spherical_hcl_to_rgb(h, c, l)
+    }
+
+    pub fn rgb_to_yuv(&mut self) {
+        let (r, g, b) = self.split();
+        rgb_to_yuv(r, g, b)
+    }
+}
+
+#[inline(always)]
+fn select(condition: bool, a: f32, b: f32) -> f32 {
+    if condition { a } else { b }
+}
+
+pub fn rgb_to_cubic_hsv(r: &mut [f32], g: &mut [f32], b: &mut [f32]) {
+    for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+        let (red, green, blue) = (*r, *g, *b);
+        let max = red.max(green).max(blue);
+        let min = red.min(green).min(blue);
+        let delta = max - min;
+        let sector = select(
+            max == red,
+            (green - blue) / delta,
+            select(max == green, (blue - red) / delta + 2., (red - green) / delta + 4.),
+        );
+        let hue = 1. + sector / 6.;
+        let hue = select(hue >= 1., hue - 1., hue);
+        let grey = delta == 0.;
+        *r = select(grey, 0., hue);
+        *g = select(grey, 0., delta / max);
+        *b = max;
+    }
+}
+
+pub fn cubic_hsv_to_rgb(h: &mut [f32], s: &mut [f32], v: &mut [f32]) {
+    for ((h, s), v) in h.iter_mut().zip(s.iter_mut()).zip(v.iter_mut()) {
+        let (hue, chroma, value) = (*h * 6., *s * *v, *v);
+        //Each channel ramps on a hexagon offset by two sectors
+        let channel = |offset: f32| {
+            let k = offset + hue;
+            let k = select(k >= 6., k - 6., k);
+            value - chroma * k.min(4. - k).clamp(0., 1.)
+        };
+        *h = channel(5.);
+        *s = channel(3.);
+        *v = channel(1.);
+    }
+}
+
+pub fn rgb_to_spherical_hcl(r: &mut [f32], g: &mut [f32], b: &mut [f32]) {
+    for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+        let (red, green, blue) = (*r, *g, *b);
+        //The rotation is chosen by the strongest secondary, ties go to yellow then cyan
+        let (cyan, magenta, yellow) = (1. - red, 1. - green, 1. - blue);
+        let strongest = cyan.max(magenta).max(yellow);
+        let (first, second) = (yellow == strongest, cyan == strongest);
+        let a = select(first, red, select(second, green, blue));
+        let b_axis = select(first, green, select(second, blue, red));
+        let c = select(first, blue, select(second, red, green));
+        let secondary = select(first, 0., select(second, 1., 2.));
+
+        let luminance = (a * a + b_axis * b_axis + c * c).sqrt();
+        let phi = (c / luminance).acos();
+        let hue_angle = b_axis.atan2(a);
+        let chroma = (((phi - 1.959_689_2) / -1.1).asin() - 1.15074) / -0.789_388_3;
+        let hue = (((hue_angle - ((PI / 4.) * (1. - chroma))) / (PI / 2.) / chroma + secondary) / 3.) % 1.;
+
+        let black = red.max(green).max(blue) == 0.;
+        let grey = chroma == 0.;
+        *r = select(black || grey, 0., hue);
+        *g = select(black || grey, 0., chroma);
+        *b = select(black, 0., luminance);
+    }
+}
+
+pub fn spherical_hcl_to_rgb(h: &mut [f32], c: &mut [f32], l: &mut [f32]) {
+    for ((h, c), l) in h.iter_mut().zip(c.iter_mut()).zip(l.iter_mut()) {
+        //Same wrap as the per Color path, a hue of 1 is 0
+        let hue = select(*h >= 1., *h - 1., *h) * 3.;
+        let (chroma, luminance) = (*c, *l);
+        let hue_angle = (PI / 2.) * (hue % 1.) * chroma + (PI / 4.) * (1. - chroma);
+        let phi = 1.959_689_2 - 1.1 * (1.15074 - 0.789_388_3 * chroma).sin();
+        let x = luminance * hue_angle.cos() * phi.sin();
+        let y = luminance * hue_angle.sin() * phi.sin();
+        let z = luminance * phi.cos();
+
+        let (first, second) = (hue < 1., hue < 2.);
+        let grey = chroma == 0.;
+        let grey_point = luminance / 3f32.sqrt();
+        *h = select(grey, grey_point, select(first, x, select(second, z, y)));
+        *c = select(grey, grey_point, select(first, y, select(second, x, z)));
+        *l = select(grey, grey_point, select(first, z, select(second, y, x)));
+    }
+}
+
+pub fn rgb_to_yuv(r: &mut [f32], g: &mut [f32], b: &mut [f32]) {
+    for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+        let y = 0.299 * *r + 0.587 * *g + 0.114 * *b;
+        let (u, v) = (0.492 * (*b - y), 0.877 * (*r - y));
+        (*r, *g, *b) = (y, u, v);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, ColorModel};
+
+    fn pixels() -> Vec<f32> {
+        (0..512).flat_map(|i| {
+            let t = i as f32 / 511.;
+            [t, (t * 7.).fract(), (t * 13.).fract(), 1.]
+        }).collect()
+    }
+
+    fn assert_matches(planes: &Planes, model: ColorModel, expected: impl Fn(Color) -> Color, input: &[f32]) {
+        for (pixel, converted) in input.chunks_exact(4).zip(planes.to_interleaved().chunks_exact(4)) {
+            let color = Color::from_array([pixel[0], pixel[1], pixel[2], pixel[3]], model);
+            let exact = expected(color).to_array();
+            for (a, b) in converted.iter().zip(exact.iter()) {
+                assert!((a - b).abs() < 1e-4, "{:?} {:?} {:?}", pixel, converted, exact);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kernels_match_per_color_path() {
+        let rgb = pixels();
+        let mut planes = Planes::from_interleaved(&rgb);
+        assert_eq!(planes.to_interleaved(), rgb);
+
+        planes.rgb_to_cubic_hsv();
+        assert_matches(&planes, ColorModel::RGBA, |c| c.to_color(ColorModel::CubicHSVA), &rgb);
+        let hsv = planes.to_interleaved();
+        planes.cubic_hsv_to_rgb();
+        assert_matches(&planes, ColorModel::CubicHSVA, |c| c.to_rgb(), &hsv);
+
+        let mut planes = Planes::from_interleaved(&rgb);
+        planes.rgb_to_spherical_hcl();
+        assert_matches(&planes, ColorModel::RGBA, |c| c.to_color(ColorModel::SphericalHCLA), &rgb);
+        let hcl = planes.to_interleaved();
+        planes.spherical_hcl_to_rgb();
+        assert_matches(&planes, ColorModel::SphericalHCLA, |c| c.to_rgb(), &hcl);
+
+        let wrapped = [1., 0.8, 1.2, 1., 0.999, 0.8, 1.2, 1.];
+        let mut planes = Planes::from_interleaved(&wrapped);
+        planes.spherical_hcl_to_rgb();
+        assert_matches(&planes, ColorModel::SphericalHCLA, |c| c.to_rgb(), &wrapped);
+
+        let mut planes = Planes::from_interleaved(&rgb);
+        planes.rgb_to_yuv();
+        assert_matches(&planes, ColorModel::RGBA, |c| c.to_yuva(), &rgb);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{Color, ColorModel};
+use crate::adaptation::{adapt_xyz, AdaptationMethod};
+use crate::illuminant::Illuminant;
+use crate::observer::{wavelength, Observer};
+use crate::rgb_space::RgbSpace;
+
+//A sampled spectral distribution, reflectance or power, over wavelengths in nanometers
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum {
+    wavelengths: Vec<f32>,
+    values: Vec<f32>,
+}
+
+impl Spectrum {
+    //Wavelengths must be ascending and match the values one to one
+    pub fn new(wavelengths: Vec<f32>, values: Vec<f32>) -> Spectrum {
+        assert_eq!(wavelengths.len(), values.len(), "every wavelength needs a value");
+        assert!(!wavelengths.is_empty(), "a spectrum needs at least one sample");
+        assert!(wavelengths.windows(2).all(|pair| pair[0] < pair[1]), "wavelengths must be ascending");
+        Spectrum { wavelengths, values }
+    }
+
+    pub fn from_samples(start: f32, step: f32, values: Vec<f32>) -> Spectrum {
+        let wavelengths = (0..values.len()).map(|i| start + step * i as f32).collect();
+        Spectrum::new(wavelengths, values)
+    }
+
+    pub fn constant(value: f32) -> Spectrum {
+        Spectrum::new(vec![380., 780.], vec![value, value])
+    }
+
+    pub fn wavelengths(&self) -> &[f32] {
+        &self.wavelengths
+    }
+
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    //Linear interpolation between samples, the end values are held outside the sampled range
+    pub fn value_at(&self, wavelength: f32) -> f32 {
+        let last = self.wavelengths.len() - 1;
+        if wavelength <= self.wavelengths[0] {
+            return self.values[0];
+        }
+        if wavelength >= self.wavelengths[last] {
+            return self.values[last];
+        }
+        let upper = self.wavelengths.partition_point(|w| *w < wavelength);
+        let (w0, w1) = (self.wavelengths[upper - 1], self.wavelengths[upper]);
+        let t = (wavelength - w0) / (w1 - w0);
+        self.values[upper - 1] + (self.values[upper] - self.values[upper - 1]) * t
+    }
+
+    pub fn resample(&self, start: f32, step: f32, count: usize) -> Spectrum {
+        let values = (0..count).map(|i| self.value_at(start + step * i as f32)).collect();
+        Spectrum::from_samples(start, step, values)
+    }
+
+    pub fn map(&self, function: impl Fn(f32) -> f32) -> Spectrum {
+        Spectrum::new(self.wavelengths.clone(), self.values.iter().map(|v| function(*v)).collect())
+    }
+
+    //Reflectance to XYZ under an illuminant, a perfect white reflector has Y = 1
+    pub fn to_xyz(&self, observer: Observer, illuminant: Illuminant) -> [f32; 3] {
+        let power = illuminant.spectrum();
+        let mut xyz = [0.; 3];
+        let mut normalization = 0.;
+        for (i, cmf) in observer.table().iter().enumerate() {
+            let nm = wavelength(i);
+            let light = power.value_at(nm);
+            let reflected = light * self.value_at(nm);
+            for (sum, weight) in xyz.iter_mut().zip(cmf.iter()) {
+                *sum += reflected * weight;
+            }
+            normalization += light * cmf[1];
+        }
+        xyz.map(|c| c / normalization)
+    }
+
+    //The reflectance seen under the illuminant by the 2° observer, adapted to the sRGB white.
+    //Colors outside sRGB are left unclipped.
+    pub fn to_color(&self, illuminant: Illuminant) -> Color {
+        self.relight(illuminant, illuminant)
+    }
+
+    //The reflectance lit by the `to` illuminant while the eye stays adapted to the `from` illuminant,
+    //so a change of light shows up as a color cast
+    pub fn relight(&self, from: Illuminant, to: Illuminant) -> Color {
+        let observer = Observer::Cie1931TwoDegree;
+        let xyz = self.to_xyz(observer, to);
+        let xyz = adapt_xyz(xyz, xyz_to_xy(from.white(observer)), RgbSpace::SRGB.white, AdaptationMethod::Bradford);
+        let [r, g, b] = RgbSpace::SRGB.from_xyz(xyz);
+        Color::from_array([r, g, b, 1.], ColorModel::RGBA)
+    }
+}
+
+pub fn xyz_to_xy(xyz: [f32; 3]) -> [f32; 2] {
+    let sum = xyz[0] + xyz[1] + xyz[2];
+    [xyz[0] / sum, xyz[1] / sum]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolation() {
+        let spectrum = Spectrum::from_samples(400., 100., vec![0., 1., 0.5]);
+        assert_eq!(spectrum.value_at(350.), 0.);
+        assert_eq!(spectrum.value_at(450.), 0.5);
+        assert_eq!(spectrum.value_at(550.), 0.75);
+        assert_eq!(spectrum.value_at(700.), 0.5);
+        assert_eq!(spectrum.resample(400., 50., 5).values(), &[0., 0.5, 1., 0.75, 0.5]);
+    }
+
+    #[test]
+    fn test_illuminant_whites() {
+        let white = Spectrum::constant(1.);
+        let cases = [
+            (Observer::Cie1931TwoDegree, Illuminant::D65, [0.95047, 1., 1.08883]),
+            (Observer::Cie1931TwoDegree, Illuminant::D50, [0.96422, 1., 0.82521]),
+            (Observer::Cie1931TwoDegree, Illuminant::A, [1.0985, 1., 0.35585]),
+            (Observer::Cie1964TenDegree, Illuminant::D65, [0.94811, 1., 1.07304]),
+        ];
+        for (observer, illuminant, expected) in cases {
+            let xyz = white.to_xyz(observer, illuminant);
+            for (value, expected) in xyz.iter().zip(expected.iter()) {
+                assert!((value - expected).abs() < 2e-3, "{:?} {:?}", illuminant, xyz);
+            }
+        }
+        //Chromaticities published with the tables
+        let published = [
+            (Illuminant::D65, [0.31271, 0.32902]),
+            (Illuminant::D50, [0.34567, 0.3585]),
+            (Illuminant::F2, [0.37208, 0.37529]),
+            (Illuminant::F7, [0.31292, 0.32933]),
+            (Illuminant::F11, [0.38052, 0.37713]),
+        ];
+        for (illuminant, expected) in published {
+            let xy = xyz_to_xy(white.to_xyz(Observer::Cie1931TwoDegree, illuminant));
+            assert!((xy[0] - expected[0]).abs() < 1e-3 && (xy[1] - expected[1]).abs() < 1e-3, "{:?} {:?}", illuminant, xy);
+        }
+        let grey = Spectrum::constant(0.5).to_color(Illuminant::D50).to_array();
+        assert!(grey.iter().take(3).all(|c| (c - 0.7354).abs() < 2e-3), "{:?}", grey);
+    }
+}
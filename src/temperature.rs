@@ -0,0 +1,172 @@
+use crate::{Color, ColorModel};
+use crate::observer::{wavelength, CIE_1931_2_DEGREE};
+use crate::cie::xy_to_xyz;
+use crate::rgb_space::RgbSpace;
+use crate::transformations::matrix_transform;
+use std::sync::OnceLock;
+
+//Second radiation constant in m·K
+const C2: f64 = 1.4388e-2;
+
+//Relative spectral radiance of a blackbody, normalized to 1 at 560nm
+pub fn planck(wavelength_nm: f32, kelvin: f32) -> f32 {
+    let radiance = |nm: f64| {
+        let meters = nm * 1e-9;
+        1. / (meters.powi(5) * ((C2 / (meters * kelvin as f64)).exp() - 1.))
+    };
+    (radiance(wavelength_nm as f64) / radiance(560.)) as f32
+}
+
+//Planck's law integrated against the CIE 1931 observer, Y = 1
+pub fn planckian_xyz(kelvin: f32) -> [f32; 3] {
+    let mut xyz = [0f64; 3];
+    for (i, cmf) in CIE_1931_2_DEGREE.iter().enumerate() {
+        let power = planck(wavelength(i), kelvin) as f64;
+        for (sum, weight) in xyz.iter_mut().zip(cmf.iter()) {
+            *sum += power * *weight as f64;
+        }
+    }
+    [xyz[0] / xyz[1], 1., xyz[2] / xyz[1]].map(|c| c as f32)
+}
+
+//Kim et al. cubic spline fit of the Planckian locus, valid from 1667K to 25000K
+pub fn planckian_xy_fast(kelvin: f32) -> [f32; 2] {
+    let t = kelvin.clamp(1667., 25000.);
+    let x = if t <= 4000. {
+        -0.2661239e9 / t.powi(3) - 0.2343589e6 / t.powi(2) + 0.8776956e3 / t + 0.17991
+    } else {
+        -3.0258469e9 / t.powi(3) + 2.107038e6 / t.powi(2) + 0.2226347e3 / t + 0.24039
+    };
+    let y = if t <= 2222. {
+        -1.1063814 * x.powi(3) - 1.3481102 * x.powi(2) + 2.1855583 * x - 0.20219683
+    } else if t <= 4000. {
+        -0.9549476 * x.powi(3) - 1.3741859 * x.powi(2) + 2.09137 * x - 0.16748867
+    } else {
+        3.081758 * x.powi(3) - 5.873387 * x.powi(2) + 3.7511299 * x - 0.37001483
+    };
+    [x, y]
+}
+
+//CIE daylight locus, valid from 4000K to 25000K
+pub fn daylight_xy(kelvin: f32) -> [f32; 2] {
+    let t = kelvin.clamp(4000., 25000.);
+    let x = if t <= 7000. {
+        -4.607e9 / t.powi(3) + 2.9678e6 / t.powi(2) + 0.09911e3 / t + 0.244063
+    } else {
+        -2.0064e9 / t.powi(3) + 1.9018e6 / t.powi(2) + 0.24748e3 / t + 0.23704
+    };
+    [x, -3. * x * x + 2.87 * x - 0.275]
+}
+
+impl Color {
+    //Blackbody color at full brightness, the brightest sRGB channel is 1
+    pub fn from_kelvin(kelvin: f32) -> Color {
+        light_from_xyz(planckian_xyz(kelvin))
+    }
+
+    pub fn from_kelvin_fast(kelvin: f32) -> Color {
+        light_from_xyz(xy_to_xyz(planckian_xy_fast(kelvin)))
+    }
+
+    //CIE D series daylight, e.g. 6504K for D65
+    pub fn daylight(kelvin: f32) -> Color {
+        light_from_xyz(xy_to_xyz(daylight_xy(kelvin)))
+    }
+
+    //Correlated color temperature and Duv of the color's sRGB chromaticity, Ohno 2013
+    pub fn cct(&self) -> (f32, f32) {
+        let [x, y, z] = self.to_xyz();
+        cct_from_uv(xyz_to_uv([x, y, z].map(|c| c as f64)))
+    }
+}
+
+//Lights are shown at full brightness, colors outside sRGB lose the negative channel
+fn light_from_xyz(xyz: [f32; 3]) -> Color {
+    let linear = matrix_transform(&RgbSpace::SRGB.from_xyz_matrix(), xyz).map(|c| c.max(0.));
+    let max = linear[0].max(linear[1]).max(linear[2]);
+    let [r, g, b] = linear.map(|c| RgbSpace::SRGB.transfer.encode(c / max));
+    Color::from_array([r, g, b, 1.], ColorModel::RGBA)
+}
+
+//CIE 1960 UCS
+fn xyz_to_uv(xyz: [f64; 3]) -> [f64; 2] {
+    let [x, y, z] = xyz;
+    let denominator = x + 15. * y + 3. * z;
+    [4. * x / denominator, 6. * y / denominator]
+}
+
+//Planckian table in 1% steps from 1000K to 40000K, as (temperatures, uv locus)
+fn planckian_table() -> &'static (Vec<f64>, Vec<[f64; 2]>) {
+    static TABLE: OnceLock<(Vec<f64>, Vec<[f64; 2]>)> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let temperatures: Vec<f64> = (0..)
+            .map(|i| 1000. * 1.01f64.powi(i))
+            .take_while(|t| *t <= 40000.)
+            .collect();
+        let locus = temperatures
+            .iter()
+            .map(|t| xyz_to_uv(planckian_xyz(*t as f32).map(|c| c as f64)))
+            .collect();
+        (temperatures, locus)
+    })
+}
+
+fn cct_from_uv(uv: [f64; 2]) -> (f32, f32) {
+    let (temperatures, locus) = planckian_table();
+    let distances: Vec<f64> = locus
+        .iter()
+        .map(|p| ((uv[0] - p[0]).powi(2) + (uv[1] - p[1]).powi(2)).sqrt())
+        .collect();
+    let nearest = (0..distances.len())
+        .min_by(|a, b| distances[*a].total_cmp(&distances[*b]))
+        .unwrap()
+        .clamp(1, distances.len() - 2);
+    let (t0, t1, t2) = (temperatures[nearest - 1], temperatures[nearest], temperatures[nearest + 1]);
+    let (d0, d1, d2) = (distances[nearest - 1], distances[nearest], distances[nearest + 1]);
+    let sign = if uv[1] >= locus[nearest][1] { 1. } else { -1. };
+
+    //Triangular solution
+    let (p0, p2) = (locus[nearest - 1], locus[nearest + 1]);
+    let l = ((p2[0] - p0[0]).powi(2) + (p2[1] - p0[1]).powi(2)).sqrt();
+    let x = (d0 * d0 - d2 * d2 + l * l) / (2. * l);
+    let cct = t0 + (t2 - t0) * x / l;
+    let duv = sign * (d0 * d0 - x * x).max(0.).sqrt();
+    if duv.abs() < 0.002 {
+        return (cct as f32, duv as f32);
+    }
+
+    //Parabolic solution
+    let denominator = (t2 - t1) * (t0 - t2) * (t1 - t0);
+    let a = (t0 * (d2 - d1) + t1 * (d0 - d2) + t2 * (d1 - d0)) / denominator;
+    let b = -(t0 * t0 * (d2 - d1) + t1 * t1 * (d0 - d2) + t2 * t2 * (d1 - d0)) / denominator;
+    let c = -(d0 * (t2 - t1) * t1 * t2 + d1 * (t0 - t2) * t0 * t2 + d2 * (t1 - t0) * t0 * t1) / denominator;
+    let cct = -b / (2. * a);
+    let duv = sign * (a * cct * cct + b * cct + c);
+    (cct as f32, duv as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blackbody_round_trip() {
+        for kelvin in [2700., 4000., 5000., 6500., 9000.] {
+            let (cct, duv) = Color::from_kelvin(kelvin).cct();
+            assert!((cct - kelvin).abs() / kelvin < 0.01, "{} {}", kelvin, cct);
+            assert!(duv.abs() < 1e-3);
+            let (fast, _) = Color::from_kelvin_fast(kelvin).cct();
+            assert!((fast - kelvin).abs() / kelvin < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_srgb_white_is_d65() {
+        let (cct, duv) = Color::rgb(1., 1., 1.).cct();
+        assert!((cct - 6504.).abs() < 30., "{}", cct);
+        assert!((duv - 0.0032).abs() < 5e-4, "{}", duv);
+        let d65 = Color::daylight(6504.).to_array();
+        assert!(d65.iter().all(|c| (c - 1.).abs() < 1e-2));
+        assert_eq!(planckian_xyz(2856.).map(|c| (c * 1e3).round()), [1098., 1000., 356.]);
+    }
+}
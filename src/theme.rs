@@ -0,0 +1,209 @@
+use crate::{Color, ColorModel};
+use crate::contrast::{fix_contrast, ContrastTarget};
+
+pub(crate) const THREE_SQRT: f32 = 1.7320508;
+
+//A single hue and chroma in spherical HCL, tones only move the luminance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TonalPalette {
+    pub hue: f32,
+    pub chroma: f32,
+}
+
+impl TonalPalette {
+    pub const fn new(hue: f32, chroma: f32) -> TonalPalette {
+        TonalPalette { hue, chroma }
+    }
+
+    //Tone runs from black at 0 to white at 1.
+    //Chroma is lowered only as far as needed to keep the tone inside the RGB cube.
+    pub fn tone(&self, tone: f32) -> Color {
+        let luminance = tone.clamp(0., 1.) * THREE_SQRT;
+        let fits = |chroma: f32| Color::spherical_hcl(self.hue, chroma, luminance).in_gamut();
+        if fits(self.chroma) {
+            return Color::spherical_hcl(self.hue, self.chroma, luminance);
+        }
+        let (mut low, mut high) = (0., self.chroma);
+        for _ in 0..24 {
+            let mid = (low + high) / 2.;
+            if fits(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Color::spherical_hcl(self.hue, low, luminance)
+    }
+
+    pub fn tones(&self, steps: usize) -> Vec<Color> {
+        (0..steps)
+            .map(|i| self.tone(i as f32 / (steps as f32 - 1.).max(1.)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scheme {
+    pub primary: Color,
+    pub on_primary: Color,
+    pub primary_container: Color,
+    pub on_primary_container: Color,
+    pub secondary: Color,
+    pub on_secondary: Color,
+    pub secondary_container: Color,
+    pub on_secondary_container: Color,
+    pub tertiary: Color,
+    pub on_tertiary: Color,
+    pub tertiary_container: Color,
+    pub on_tertiary_container: Color,
+    pub error: Color,
+    pub on_error: Color,
+    pub error_container: Color,
+    pub on_error_container: Color,
+    pub surface: Color,
+    pub on_surface: Color,
+    pub surface_variant: Color,
+    pub on_surface_variant: Color,
+    pub outline: Color,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Theme {
+    pub seed: Color,
+    pub primary: TonalPalette,
+    pub secondary: TonalPalette,
+    pub tertiary: TonalPalette,
+    pub neutral: TonalPalette,
+    pub neutral_variant: TonalPalette,
+    pub error: TonalPalette,
+    pub light: Scheme,
+    pub dark: Scheme,
+}
+
+//Minimum contrast ratios for text roles and for outlines against the surface
+pub const TEXT_CONTRAST: f32 = 4.5;
+pub const OUTLINE_CONTRAST: f32 = 3.;
+
+//Tones for (role, on role, container, on container)
+const LIGHT_TONES: [f32; 4] = [0.4, 1., 0.9, 0.1];
+const DARK_TONES: [f32; 4] = [0.8, 0.2, 0.3, 0.9];
+
+impl Theme {
+    pub fn from_seed(seed: Color) -> Theme {
+        let [hue, chroma, _, _] = seed.convert_color(ColorModel::SphericalHCLA).components;
+        let primary = TonalPalette::new(hue, chroma.max(0.3));
+        let secondary = TonalPalette::new(hue, primary.chroma / 3.);
+        let tertiary = TonalPalette::new((hue + 1. / 6.) % 1., primary.chroma / 2.);
+        let neutral = TonalPalette::new(hue, 0.03);
+        let neutral_variant = TonalPalette::new(hue, 0.08);
+        let error = TonalPalette::new(0., 0.8);
+        let palettes = [primary, secondary, tertiary, error, neutral, neutral_variant];
+        Theme {
+            seed,
+            primary,
+            secondary,
+            tertiary,
+            neutral,
+            neutral_variant,
+            error,
+            light: Scheme::build(palettes, LIGHT_TONES, [0.98, 0.1, 0.9, 0.3, 0.5]),
+            dark: Scheme::build(palettes, DARK_TONES, [0.06, 0.9, 0.3, 0.8, 0.6]),
+        }
+    }
+}
+
+impl Scheme {
+    //Surface tones are (surface, on surface, surface variant, on surface variant, outline)
+    fn build(palettes: [TonalPalette; 6], tones: [f32; 4], surface_tones: [f32; 5]) -> Scheme {
+        let [primary, secondary, tertiary, error, neutral, neutral_variant] = palettes;
+        let [p, on_p, pc, on_pc] = role_group(primary, tones);
+        let [s, on_s, sc, on_sc] = role_group(secondary, tones);
+        let [t, on_t, tc, on_tc] = role_group(tertiary, tones);
+        let [e, on_e, ec, on_ec] = role_group(error, tones);
+        let [surface, on_surface, variant, on_variant, outline] = surface_tones;
+        Scheme {
+            primary: p,
+            on_primary: on_p,
+            primary_container: pc,
+            on_primary_container: on_pc,
+            secondary: s,
+            on_secondary: on_s,
+            secondary_container: sc,
+            on_secondary_container: on_sc,
+            tertiary: t,
+            on_tertiary: on_t,
+            tertiary_container: tc,
+            on_tertiary_container: on_tc,
+            error: e,
+            on_error: on_e,
+            error_container: ec,
+            on_error_container: on_ec,
+            surface: neutral.tone(surface),
+            on_surface: paired_tone(neutral, surface, on_surface, TEXT_CONTRAST),
+            surface_variant: neutral_variant.tone(variant),
+            on_surface_variant: paired_tone(neutral_variant, variant, on_variant, TEXT_CONTRAST),
+            outline: paired_tone(neutral_variant, surface, outline, OUTLINE_CONTRAST),
+        }
+    }
+}
+
+fn role_group(palette: TonalPalette, tones: [f32; 4]) -> [Color; 4] {
+    let [role, on_role, container, on_container] = tones;
+    [
+        palette.tone(role),
+        paired_tone(palette, role, on_role, TEXT_CONTRAST),
+        palette.tone(container),
+        paired_tone(palette, container, on_container, TEXT_CONTRAST),
+    ]
+}
+
+//Every ratio the schemes ask for is reachable between a palette's black and white, a miss still
+//keeps the closest tone
+fn paired_tone(palette: TonalPalette, background: f32, foreground: f32, ratio: f32) -> Color {
+    fix_contrast(palette.tone(foreground), palette.tone(background), ContrastTarget::Wcag(ratio))
+        .unwrap_or_else(|closest| closest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contrast::contrast_ratio;
+    use crate::constants::{BLUE, ORANGE, MOSS};
+
+    #[test]
+    fn test_palette_hue_is_stable() {
+        let palette = TonalPalette::new(0.6, 0.9);
+        for color in palette.tones(11) {
+            let rgb = color.to_rgb().components;
+            assert!(rgb.iter().all(|c| (-1e-3..=1.001).contains(c)));
+            if color.components[1] > 0. {
+                assert_eq!(color.components[0], 0.6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_scheme_contrast() {
+        for seed in [BLUE, ORANGE, MOSS] {
+            let theme = Theme::from_seed(seed);
+            for scheme in [theme.light, theme.dark] {
+                let pairs = [
+                    (scheme.primary, scheme.on_primary),
+                    (scheme.primary_container, scheme.on_primary_container),
+                    (scheme.secondary, scheme.on_secondary),
+                    (scheme.tertiary_container, scheme.on_tertiary_container),
+                    (scheme.error, scheme.on_error),
+                    (scheme.surface, scheme.on_surface),
+                    (scheme.surface_variant, scheme.on_surface_variant),
+                ];
+                for (background, foreground) in pairs {
+                    assert!(contrast_ratio(background, foreground) >= TEXT_CONTRAST);
+                }
+                assert!(contrast_ratio(scheme.surface, scheme.outline) >= OUTLINE_CONTRAST);
+            }
+        }
+    }
+}
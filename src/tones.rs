@@ -0,0 +1,108 @@
+use crate::{Color, ColorModel};
+use crate::constants::{TRANSPARENT, WHITE};
+use crate::transformations::lerp;
+
+//Tones are built in spherical HWB, the same model the named constants use.
+//Every method returns the color in its original model.
+impl Color {
+    //Mixes towards white
+    pub fn tint(&self, amount: f32) -> Color {
+        self.hwb_mix(1., 0., amount)
+    }
+
+    //Mixes towards black
+    pub fn shade(&self, amount: f32) -> Color {
+        self.hwb_mix(0., 1., amount)
+    }
+
+    //Mixes towards the middle grey
+    pub fn tone(&self, amount: f32) -> Color {
+        self.hwb_mix(0.5, 0.5, amount)
+    }
+
+    //Light to dark ramp through the color itself, pure white and black are left out
+    pub fn tonal_ramp(&self, steps: usize) -> Vec<Color> {
+        (0..steps)
+            .map(|i| {
+                let t = 2. * (i + 1) as f32 / (steps + 1) as f32 - 1.;
+                if t < 0. {
+                    self.tint(-t)
+                } else {
+                    self.shade(t)
+                }
+            })
+            .collect()
+    }
+
+    fn hwb_mix(&self, white: f32, black: f32, amount: f32) -> Color {
+        let [h, w, b, a] = self.convert_color(ColorModel::SphericalHWBA).components;
+        Color {
+            components: [h, lerp(w, white, amount), lerp(b, black, amount), a],
+            color_type: ColorModel::SphericalHWBA,
+        }
+        .convert_color(self.color_type)
+    }
+}
+
+//Regenerates a QUATERNARY_COLORS style table: a neutral row followed by one row per hue.
+//Each hue row starts with the pure color, the remaining tones run from the half tint through
+//the half tone to the half shade. tone_table(24, 4) reproduces QUATERNARY_COLORS.
+pub fn tone_table(hues: usize, tones: usize) -> Vec<Vec<Color>> {
+    let mut table = Vec::with_capacity(hues + 1);
+    let neutral = std::iter::once(TRANSPARENT).chain(tone_steps(tones).map(|t| WHITE.shade(t))).collect();
+    table.push(neutral);
+    for i in 0..hues {
+        let pure = Color::spherical_hwb(i as f32 / hues as f32, 0., 0.);
+        let row = std::iter::once(pure)
+            .chain(tone_steps(tones).map(|t| {
+                if t <= 0.5 {
+                    pure.tint((0.5 - t) / (1. - t)).tone(t)
+                } else {
+                    pure.shade((t - 0.5) / t).tone(1. - t)
+                }
+            }))
+            .collect();
+        table.push(row);
+    }
+    table
+}
+
+fn tone_steps(tones: usize) -> impl Iterator<Item = f32> {
+    let count = tones.saturating_sub(1);
+    (0..count).map(move |i| if count == 1 { 0.5 } else { i as f32 / (count - 1) as f32 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::tone_table;
+    use crate::constants::{MAROON, QUATERNARY_COLORS, RED, SALMON, BURGUNDY};
+
+    #[test]
+    fn test_tones_match_constants() {
+        assert_eq!(RED.tint(0.5), SALMON);
+        assert_eq!(RED.shade(0.5), MAROON);
+        assert_eq!(RED.tone(0.5), BURGUNDY);
+    }
+
+    #[test]
+    fn test_tonal_ramp() {
+        let ramp = RED.tonal_ramp(9);
+        assert_eq!(ramp.len(), 9);
+        assert_eq!(ramp[4], RED);
+        assert_eq!(ramp[0].to_array()[2], 0.);
+        assert_eq!(ramp[8].to_array()[1], 0.);
+    }
+
+    #[test]
+    fn test_tone_table() {
+        let table = tone_table(24, 4);
+        assert_eq!(table.len(), QUATERNARY_COLORS.len());
+        for (row, expected) in table.iter().zip(QUATERNARY_COLORS.iter()) {
+            for (color, expected) in row.iter().zip(expected.iter()) {
+                for (a, b) in color.to_array().iter().zip(expected.to_array().iter()) {
+                    assert!((a - b).abs() < 1e-6);
+                }
+            }
+        }
+    }
+}
@@ -6,6 +6,8 @@ enum NormalCurve {
     Quadratiic(f32, f32),
     Cubic(f32, f32, f32, f32),
     Composed(Vec<(NormalCurve, f32, f32)>),
+    //Evenly spaced samples over 0 to 1, linearly interpolated
+    Sampled(Vec<f32>),
 }
 
 impl NormalCurve {
@@ -15,19 +17,21 @@ impl NormalCurve {
 }
 
 trait Mapping {
-    fn map_curve(self, curve: NormalCurve) -> f32;
+    fn map_curve(self, curve: &NormalCurve) -> f32;
     fn quadratic_mapping(self, x: f32, y: f32) -> f32;
     fn cubic_mapping(self, x1: f32, y1: f32, x2: f32, y2: f32) -> f32;
-    fn composed_mapping(self, curves: Vec<(NormalCurve, f32, f32)>) -> f32;
+    fn composed_mapping(self, curves: &[(NormalCurve, f32, f32)]) -> f32;
+    fn sampled_mapping(self, samples: &[f32]) -> f32;
 }
 impl Mapping for f32 {
-    fn map_curve(self, curve: NormalCurve) -> f32 {
+    fn map_curve(self, curve: &NormalCurve) -> f32 {
         match curve {
             NormalCurve::Linear => self,
-            NormalCurve::Power(a) => self.powf(a),
-            NormalCurve::Quadratiic(x, y) => self.quadratic_mapping(x, y),
-            NormalCurve::Cubic(x1, y1, x2, y2) => self.cubic_mapping(x1, y1, x2, y2),
+            NormalCurve::Power(a) => self.powf(*a),
+            NormalCurve::Quadratiic(x, y) => self.quadratic_mapping(*x, *y),
+            NormalCurve::Cubic(x1, y1, x2, y2) => self.cubic_mapping(*x1, *y1, *x2, *y2),
             NormalCurve::Composed(curves) => self.composed_mapping(curves),
+            NormalCurve::Sampled(samples) => self.sampled_mapping(samples),
         }
     }
     fn quadratic_mapping(self, x1: f32, y1: f32) -> f32 {
@@ -43,9 +47,19 @@ impl Mapping for f32 {
         return 0.0;
     }
 
-    fn composed_mapping(self, curves: Vec<(NormalCurve, f32, f32)>) -> f32 {
+    fn composed_mapping(self, curves: &[(NormalCurve, f32, f32)]) -> f32 {
         return 0.0;
     }
+
+    fn sampled_mapping(self, samples: &[f32]) -> f32 {
+        if samples.len() < 2 {
+            return samples.first().copied().unwrap_or(self);
+        }
+        let position = self.clamp(0., 1.) * (samples.len() - 1) as f32;
+        let index = (position as usize).min(samples.len() - 2);
+        let t = position - index as f32;
+        samples[index] + (samples[index + 1] - samples[index]) * t
+    }
 }
 
 pub struct DefinedColor {
@@ -103,17 +117,40 @@ impl DefinedColor {
         };
     }
 
+    //One curve per component given as evenly spaced samples over 0 to 1, e.g. loaded from a 1D LUT
+    pub fn sampled(color: Color, samples: [Vec<f32>; 4]) -> DefinedColor {
+        DefinedColor {
+            color,
+            mapping_curve: samples.map(NormalCurve::Sampled),
+        }
+    }
+
+    //Evaluates each component's curve at `count` evenly spaced points from 0 to 1
+    pub fn sample_curves(&self, count: usize) -> [Vec<f32>; 4] {
+        let last = (count.max(2) - 1) as f32;
+        [0, 1, 2, 3].map(|component| {
+            (0..count)
+                .map(|i| (i as f32 / last).map_curve(&self.mapping_curve[component]))
+                .collect()
+        })
+    }
+
     pub fn collapse_color(&self) -> Color {
         Color {
-            components: [
-                self.color.components[0].map_curve(self.mapping_curve[0].clone()),
-                self.color.components[1].map_curve(self.mapping_curve[1].clone()),
-                self.color.components[2].map_curve(self.mapping_curve[2].clone()),
-                self.color.components[3].map_curve(self.mapping_curve[3].clone()),
-            ],
+            components: self.map_components(self.color.components),
             color_type: self.color.color_type,
         }
     }
+
+    //Runs any four components through this color's curves, for applying the curves to pixel buffers
+    pub fn map_components(&self, components: [f32; 4]) -> [f32; 4] {
+        [
+            components[0].map_curve(&self.mapping_curve[0]),
+            components[1].map_curve(&self.mapping_curve[1]),
+            components[2].map_curve(&self.mapping_curve[2]),
+            components[3].map_curve(&self.mapping_curve[3]),
+        ]
+    }
 }
 
 
@@ -155,6 +192,41 @@ where
     a + (b - a) * percent
 }
 
+//sRGB transfer functions, encoded values are what Color RGBA stores
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+pub fn matrix_transform(matrix: &[[f32; 3]; 3], vector: [f32; 3]) -> [f32; 3] {
+    matrix.map(|row| row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2])
+}
+
+pub fn multiply_matrices(a: &[[f32; 3]; 3], b: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    [0, 1, 2].map(|i| [0, 1, 2].map(|j| a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j]))
+}
+
+pub fn invert_matrix(matrix: &[[f32; 3]; 3]) -> [[f32; 3]; 3] {
+    let [[a, b, c], [d, e, f], [g, h, i]] = *matrix;
+    let determinant = a * (e * i - f * h) - b * (d * i - f * g) + c * (d * h - e * g);
+    [
+        [(e * i - f * h) / determinant, (c * h - b * i) / determinant, (b * f - c * e) / determinant],
+        [(f * g - d * i) / determinant, (a * i - c * g) / determinant, (c * d - a * f) / determinant],
+        [(d * h - e * g) / determinant, (b * g - a * h) / determinant, (a * e - b * d) / determinant],
+    ]
+}
+
 //Space Conversions
 pub fn cylindrical_to_xyz(theta: f32, r: f32, z: f32) -> (f32, f32, f32) {
     let theta_radians = theta * std::f32::consts::TAU; // TAU is 2π
@@ -0,0 +1,213 @@
+use crate::Color;
+use crate::illuminant::Illuminant;
+use crate::observer::{wavelength, Observer, SAMPLE_COUNT, WAVELENGTH_START, WAVELENGTH_STEP};
+use crate::rgb_space::RgbSpace;
+use crate::spectrum::Spectrum;
+use crate::transformations::{matrix_transform, srgb_to_linear};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upsampling {
+    //Smits 1999, a piecewise sum of white, secondary and primary basis spectra
+    Smits,
+    //Jakob and Hanika 2019, a sigmoid of a quadratic in wavelength, smooth and always within 0 to 1
+    JakobHanika,
+}
+
+//Smits' basis spectra over 10 equal bins from 380nm to 720nm
+const SMITS_START: f32 = 380.;
+const SMITS_BIN: f32 = 34.;
+const SMITS_WHITE: [f32; 10] = [1., 1., 0.9999, 0.9993, 0.9992, 0.9998, 1., 1., 1., 1.];
+const SMITS_CYAN: [f32; 10] = [0.971, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0., 0., 0.];
+const SMITS_MAGENTA: [f32; 10] = [1., 1., 0.9685, 0.2229, 0., 0.0458, 0.8369, 1., 1., 0.9959];
+const SMITS_YELLOW: [f32; 10] = [0.0001, 0., 0.1088, 0.6651, 1., 1., 0.9996, 0.9586, 0.9685, 0.984];
+const SMITS_RED: [f32; 10] = [0.1012, 0.0515, 0., 0., 0., 0., 0.8325, 1.0149, 1.0149, 1.0149];
+const SMITS_GREEN: [f32; 10] = [0., 0., 0.0273, 0.7937, 1., 0.9418, 0.1719, 0., 0., 0.0025];
+const SMITS_BLUE: [f32; 10] = [1., 1., 0.8916, 0.3323, 0., 0., 0.0003, 0.0369, 0.0483, 0.0496];
+
+impl Color {
+    //A reflectance spectrum that looks like the color under D65, alpha is dropped
+    //and the linear sRGB channels are clamped to the 0 to 1 range a reflectance can reach
+    pub fn to_spectrum(&self, method: Upsampling) -> Spectrum {
+        let [r, g, b, _] = self.to_rgb().components;
+        let linear = [r, g, b].map(|c| srgb_to_linear(c.clamp(0., 1.)));
+        match method {
+            Upsampling::Smits => smits(linear),
+            Upsampling::JakobHanika => {
+                let coefficients = fit_sigmoid_polynomial(linear);
+                let values = (0..SAMPLE_COUNT).map(|i| sigmoid_polynomial(coefficients, wavelength(i))).collect();
+                Spectrum::from_samples(WAVELENGTH_START, WAVELENGTH_STEP, values)
+            }
+        }
+    }
+}
+
+fn smits(rgb: [f32; 3]) -> Spectrum {
+    let [r, g, b] = rgb;
+    let mut values = [0f32; 10];
+    let mut add = |weight: f32, basis: &[f32; 10]| {
+        for (value, sample) in values.iter_mut().zip(basis.iter()) {
+            *value += weight * sample;
+        }
+    };
+    if r <= g && r <= b {
+        add(r, &SMITS_WHITE);
+        if g <= b {
+            add(g - r, &SMITS_CYAN);
+            add(b - g, &SMITS_BLUE);
+        } else {
+            add(b - r, &SMITS_CYAN);
+            add(g - b, &SMITS_GREEN);
+        }
+    } else if g <= r && g <= b {
+        add(g, &SMITS_WHITE);
+        if r <= b {
+            add(r - g, &SMITS_MAGENTA);
+            add(b - r, &SMITS_BLUE);
+        } else {
+            add(b - g, &SMITS_MAGENTA);
+            add(r - b, &SMITS_RED);
+        }
+    } else {
+        add(b, &SMITS_WHITE);
+        if r <= g {
+            add(r - b, &SMITS_YELLOW);
+            add(g - r, &SMITS_GREEN);
+        } else {
+            add(g - b, &SMITS_YELLOW);
+            add(r - g, &SMITS_RED);
+        }
+    }
+    Spectrum::from_samples(SMITS_START + SMITS_BIN / 2., SMITS_BIN, values.to_vec())
+}
+
+//Wavelength is remapped from 380nm..780nm to 0..1 so the coefficients stay well conditioned
+pub fn sigmoid_polynomial(coefficients: [f32; 3], wavelength_nm: f32) -> f32 {
+    let t = (wavelength_nm - WAVELENGTH_START) / (WAVELENGTH_STEP * (SAMPLE_COUNT - 1) as f32);
+    let x = (coefficients[0] * t + coefficients[1]) * t + coefficients[2];
+    if x.is_infinite() {
+        return if x > 0. { 1. } else { 0. };
+    }
+    0.5 + x / (2. * (1. + x * x).sqrt())
+}
+
+//Gauss-Newton fit of the sigmoid polynomial to linear sRGB under D65.
+//Colors on the edge of the cube are only approached, the sigmoid never reaches 0 or 1.
+pub fn fit_sigmoid_polynomial(linear_rgb: [f32; 3]) -> [f32; 3] {
+    let weights = rgb_weights();
+    let target = linear_rgb.map(|c| c as f64);
+    let residual = |coefficients: [f64; 3]| {
+        let mut rgb = [0f64; 3];
+        for (i, weight) in weights.iter().enumerate() {
+            let value = sigmoid_polynomial(coefficients.map(|c| c as f32), wavelength(i)) as f64;
+            for (sum, w) in rgb.iter_mut().zip(weight.iter()) {
+                *sum += value * w;
+            }
+        }
+        [rgb[0] - target[0], rgb[1] - target[1], rgb[2] - target[2]]
+    };
+    let norm = |r: [f64; 3]| r[0] * r[0] + r[1] * r[1] + r[2] * r[2];
+
+    let mut coefficients = [0f64; 3];
+    let mut current = residual(coefficients);
+    for _ in 0..100 {
+        if norm(current) < 1e-12 {
+            break;
+        }
+        let mut jacobian = [[0f64; 3]; 3];
+        for j in 0..3 {
+            let mut shifted = coefficients;
+            shifted[j] += 1e-4;
+            let moved = residual(shifted);
+            for (row, (a, b)) in jacobian.iter_mut().zip(moved.iter().zip(current.iter())) {
+                row[j] = (a - b) / 1e-4;
+            }
+        }
+        let Some(step) = solve(jacobian, current) else { break };
+        //Halve the step until the error drops
+        let mut scale = 1.;
+        let mut improved = false;
+        while scale > 1e-4 {
+            let candidate = [0, 1, 2].map(|j| coefficients[j] - scale * step[j]);
+            let next = residual(candidate);
+            if norm(next) < norm(current) {
+                coefficients = candidate;
+                current = next;
+                improved = true;
+                break;
+            }
+            scale /= 2.;
+        }
+        if !improved {
+            break;
+        }
+    }
+    coefficients.map(|c| c as f32)
+}
+
+//Reflectance samples on the observer grid to linear sRGB under D65, a perfect white gives 1
+fn rgb_weights() -> [[f64; 3]; SAMPLE_COUNT] {
+    let power = Illuminant::D65.spectrum();
+    let table = Observer::Cie1931TwoDegree.table();
+    let white = table.iter().enumerate().map(|(i, cmf)| (power.values()[i] * cmf[1]) as f64).sum::<f64>();
+    let to_rgb = RgbSpace::SRGB.from_xyz_matrix();
+    let mut weights = [[0f64; 3]; SAMPLE_COUNT];
+    for (i, (weight, cmf)) in weights.iter_mut().zip(table.iter()).enumerate() {
+        let rgb = matrix_transform(&to_rgb, cmf.map(|c| c * power.values()[i]));
+        *weight = rgb.map(|c| c as f64 / white);
+    }
+    weights
+}
+
+//Cramer's rule for the 3x3 Gauss-Newton system
+fn solve(matrix: [[f64; 3]; 3], vector: [f64; 3]) -> Option<[f64; 3]> {
+    let determinant = |m: [[f64; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1]) - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+    let base = determinant(matrix);
+    if base.abs() < 1e-18 {
+        return None;
+    }
+    Some([0, 1, 2].map(|column| {
+        let mut replaced = matrix;
+        for (row, value) in replaced.iter_mut().zip(vector.iter()) {
+            row[column] = *value;
+        }
+        determinant(replaced) / base
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(color: Color, method: Upsampling) -> [f32; 4] {
+        color.to_spectrum(method).to_color(Illuminant::D65).to_array()
+    }
+
+    #[test]
+    fn test_round_trip_under_d65() {
+        let colors = [Color::rgb(0.8, 0.3, 0.2), Color::rgb(0.2, 0.6, 0.4), Color::rgb(0.3, 0.4, 0.9), Color::rgb(0.5, 0.5, 0.5)];
+        for color in colors {
+            let expected = color.to_array();
+            let smits = round_trip(color, Upsampling::Smits);
+            let jakob = round_trip(color, Upsampling::JakobHanika);
+            for i in 0..3 {
+                assert!((smits[i] - expected[i]).abs() < 0.05, "{:?} {:?}", color, smits);
+                assert!((jakob[i] - expected[i]).abs() < 5e-3, "{:?} {:?}", color, jakob);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reflectance_stays_physical() {
+        for color in [Color::rgb(1., 0., 0.), Color::rgb(0., 0., 1.), Color::rgb(1., 1., 1.), Color::rgb(0., 0., 0.)] {
+            let spectrum = color.to_spectrum(Upsampling::JakobHanika);
+            assert!(spectrum.values().iter().all(|v| (0. ..=1.).contains(v)));
+            let back = spectrum.to_color(Illuminant::D65).to_array();
+            for (a, b) in back.iter().zip(color.to_array().iter()) {
+                assert!((a - b).abs() < 0.05, "{:?} {:?}", color, back);
+            }
+        }
+    }
+}